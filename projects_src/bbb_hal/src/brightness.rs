@@ -0,0 +1,102 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Highest brightness level, the display being lit all the time.
+pub const MAX_BRIGHTNESS: u8 = 15;
+
+/// Shared brightness level of a display, from 0 (off) to [`MAX_BRIGHTNESS`].
+///
+/// Clones share the same level, so the thread lighting the display picks up
+/// a change on its next cycle.
+///
+/// # Fields
+/// * `level` - The current brightness level.
+///
+/// # Examples
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use bbb_hal::brightness::Brightness;
+///
+/// let brightness = Brightness::default();
+/// brightness.clone().set(5)?;
+/// assert_eq!(brightness.scale(Duration::from_millis(3)), Duration::from_millis(1));
+/// assert!(brightness.set(16).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Brightness {
+    level: Arc<AtomicU8>,
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self { level: Arc::new(AtomicU8::new(MAX_BRIGHTNESS)) }
+    }
+}
+
+impl Brightness {
+    /// Get the current brightness level.
+    pub fn get(&self) -> u8 {
+        self.level.load(Ordering::Relaxed)
+    }
+
+    /// Change the brightness level.
+    ///
+    /// # Arguments
+    /// * `level` - The new level, from 0 (off) to [`MAX_BRIGHTNESS`].
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the level is out of range.
+    pub fn set(&self, level: u8) -> Result<(), Box<dyn Error>> {
+
+        validate(level)?;
+        self.level.store(level, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Get the part of a period the display has to be lit for the current level.
+    ///
+    /// # Arguments
+    /// * `period` - The full period, e.g. the dwell time of a digit.
+    ///
+    /// # Returns
+    /// * `Duration` - The on-time, from zero to the full period.
+    pub fn scale(&self, period: Duration) -> Duration {
+        scale(self.get(), period)
+    }
+}
+
+/// Check a brightness level.
+///
+/// # Arguments
+/// * `level` - The level, from 0 (off) to [`MAX_BRIGHTNESS`].
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if the level is out of range.
+pub fn validate(level: u8) -> Result<(), Box<dyn Error>> {
+    if level > MAX_BRIGHTNESS {
+        return Err(format!("Brightness {} out of range, expected 0 to {}", level, MAX_BRIGHTNESS).into());
+    }
+    Ok(())
+}
+
+/// Get the part of a period a display has to be lit for at a level.
+///
+/// # Arguments
+/// * `level` - The brightness level, from 0 (off) to [`MAX_BRIGHTNESS`].
+/// * `period` - The full period, e.g. the dwell time of a digit.
+///
+/// # Returns
+/// * `Duration` - The on-time, from zero to the full period.
+pub fn scale(level: u8, period: Duration) -> Duration {
+    period * level.min(MAX_BRIGHTNESS) as u32 / MAX_BRIGHTNESS as u32
+}
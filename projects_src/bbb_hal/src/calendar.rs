@@ -0,0 +1,100 @@
+//! Proleptic Gregorian calendar arithmetic on day counts since 1970-01-01,
+//! shared by the RTC driver and the time zone conversions.
+
+/// Whether a year has a February 29.
+pub fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Number of days of a month.
+///
+/// # Arguments
+/// * `year` - The year.
+/// * `month` - The month, from 1 to 12.
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Day of the week of a day count since 1970-01-01, a Thursday, 0 being Sunday.
+pub fn weekday(days: i64) -> u32 {
+    (days + 4).rem_euclid(7) as u32
+}
+
+/// Days since 1970-01-01 of a date.
+///
+/// # Arguments
+/// * `year` - The year.
+/// * `month` - The month, from 1 to 12.
+/// * `day` - The day of the month, from 1 to 31.
+///
+/// # Returns
+/// * `i64` - The days, negative before 1970.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of a day count since 1970-01-01.
+///
+/// # Arguments
+/// * `days` - The days, negative before 1970.
+///
+/// # Returns
+/// * `(i64, u32, u32)` - The year, month and day of the month.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_dates() {
+        let dates = [
+            ((1970, 1, 1), 0),
+            ((1969, 12, 31), -1),
+            ((2000, 2, 29), 11_016),
+            ((2026, 10, 17), 20_743),
+            ((2100, 3, 1), 47_541),
+        ];
+        for (date, days) in dates {
+            assert_eq!(days_from_civil(date.0, date.1, date.2), days, "{:?}", date);
+            assert_eq!(civil_from_days(days), date, "{}", days);
+        }
+        // a Saturday
+        assert_eq!(weekday(20_743), 6);
+    }
+
+    #[test]
+    fn round_trip_over_four_centuries() {
+        let mut days = days_from_civil(1900, 1, 1);
+        for year in 1900..2300 {
+            for month in 1..=12 {
+                for day in 1..=days_in_month(year, month) {
+                    assert_eq!(days_from_civil(year, month, day), days);
+                    assert_eq!(civil_from_days(days), (year, month, day));
+                    days += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::seven_segment::Segment;
+
+/// The segments lit on one digit, stored as a bitmask.
+///
+/// Bit 0 is segment `A` up to bit 6 for segment `G`, bit 7 is the decimal point:
+///
+/// ```text
+///      A
+///     ---
+///  F |   | B
+///     -G-
+///  E |   | C
+///     ---   . DP
+///      D
+/// ```
+///
+/// # Examples
+/// ```
+/// use bbb_hal::{font::Glyph, seven_segment::Segment};
+///
+/// let glyph = Glyph::from_char('F').unwrap();
+/// assert!(glyph.contains(Segment::G));
+/// assert!(!glyph.with_dp(true).with_dp(false).contains(Segment::DP));
+/// assert_eq!(Glyph::from_char('M'), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Glyph(u8);
+
+impl Glyph {
+    pub const BLANK: Glyph = Glyph(0x00);
+    pub const MINUS: Glyph = Glyph(0x40);
+    pub const UNDERSCORE: Glyph = Glyph(0x08);
+    pub const DEGREE: Glyph = Glyph(0x63);
+
+    /// Hexadecimal digits `0`..`F`, indexed by their value.
+    const HEX: [Glyph; 16] = [
+        Glyph(0x3F), Glyph(0x06), Glyph(0x5B), Glyph(0x4F),
+        Glyph(0x66), Glyph(0x6D), Glyph(0x7D), Glyph(0x07),
+        Glyph(0x7F), Glyph(0x6F), Glyph(0x77), Glyph(0x7C),
+        Glyph(0x39), Glyph(0x5E), Glyph(0x79), Glyph(0x71),
+    ];
+
+    /// Create a glyph from its raw bitmask.
+    pub const fn from_bits(bits: u8) -> Self {
+        Glyph(bits)
+    }
+
+    /// Get the raw bitmask of the glyph.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Create a glyph lighting the given segments.
+    ///
+    /// # Arguments
+    /// * `segments` - The segments to light.
+    ///
+    /// # Returns
+    /// * `Glyph` - The glyph lighting exactly these segments.
+    pub fn from_segments(segments: &[Segment]) -> Self {
+        Glyph(segments.iter().fold(0, |bits, &seg| bits | mask(seg)))
+    }
+
+    /// Get the glyph of a hexadecimal digit.
+    ///
+    /// # Arguments
+    /// * `digit` - The digit value, from 0 to 15.
+    ///
+    /// # Returns
+    /// * `Option<Glyph>` - The glyph, or `None` if the value is not a hexadecimal digit.
+    pub fn from_digit(digit: u8) -> Option<Self> {
+        Self::HEX.get(digit as usize).copied()
+    }
+
+    /// Get the glyph of a character.
+    ///
+    /// Letters use the usual seven-segment approximations, in lower case
+    /// where the upper case form would be mistaken for a digit (`b`, `d`).
+    /// Letters that cannot be told apart on seven segments (`K`, `M`, `V`,
+    /// `W`, `X`) have no glyph.
+    ///
+    /// # Arguments
+    /// * `c` - The character to show.
+    ///
+    /// # Returns
+    /// * `Option<Glyph>` - The glyph, or `None` if the character cannot be shown.
+    pub fn from_char(c: char) -> Option<Self> {
+
+        if let Some(digit) = c.to_digit(10) {
+            return Self::from_digit(digit as u8);
+        }
+
+        let bits = match c {
+            ' ' => 0x00,
+            '-' => 0x40,
+            '_' => 0x08,
+            '°' => 0x63,
+            'A' | 'a' => 0x77,
+            'B' | 'b' => 0x7C,
+            'C' => 0x39,
+            'c' => 0x58,
+            'D' | 'd' => 0x5E,
+            'E' | 'e' => 0x79,
+            'F' | 'f' => 0x71,
+            'G' | 'g' => 0x3D,
+            'H' => 0x76,
+            'h' => 0x74,
+            'I' => 0x30,
+            'i' => 0x10,
+            'J' | 'j' => 0x1E,
+            'L' | 'l' => 0x38,
+            'N' => 0x37,
+            'n' => 0x54,
+            'O' => 0x3F,
+            'o' => 0x5C,
+            'P' | 'p' => 0x73,
+            'Q' | 'q' => 0x67,
+            'R' | 'r' => 0x50,
+            'S' | 's' => 0x6D,
+            'T' | 't' => 0x78,
+            'U' => 0x3E,
+            'u' => 0x1C,
+            'Y' | 'y' => 0x6E,
+            'Z' | 'z' => 0x5B,
+            _ => return None,
+        };
+
+        Some(Glyph(bits))
+    }
+
+    /// Whether the glyph lights a segment.
+    pub fn contains(self, seg: Segment) -> bool {
+        self.0 & mask(seg) != 0
+    }
+
+    /// Get the glyph with a segment turned on or off.
+    pub fn with_segment(self, seg: Segment, on: bool) -> Self {
+        if on {
+            Glyph(self.0 | mask(seg))
+        } else {
+            Glyph(self.0 & !mask(seg))
+        }
+    }
+
+    /// Get the glyph with the decimal point turned on or off.
+    pub fn with_dp(self, on: bool) -> Self {
+        self.with_segment(Segment::DP, on)
+    }
+}
+
+/// Get the bit of a segment in a glyph bitmask.
+fn mask(seg: Segment) -> u8 {
+    match seg {
+        Segment::A => 0x01,
+        Segment::B => 0x02,
+        Segment::C => 0x04,
+        Segment::D => 0x08,
+        Segment::E => 0x10,
+        Segment::F => 0x20,
+        Segment::G => 0x40,
+        Segment::DP => 0x80,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_digits_match_from_digit() {
+        for c in "0123456789ABCDEFabdef".chars() {
+            let value = c.to_digit(16).unwrap() as u8;
+            assert_eq!(Glyph::from_char(c), Glyph::from_digit(value), "{:?}", c);
+        }
+        // a lower case c stays in the lower half, unlike the hexadecimal digit
+        assert_eq!(Glyph::from_char('c'), Some(Glyph::from_segments(&[Segment::D, Segment::E, Segment::G])));
+        assert_eq!(Glyph::from_digit(16), None);
+    }
+
+    #[test]
+    fn letters() {
+        assert_eq!(Glyph::from_char('H'), Some(Glyph::from_segments(&[Segment::B, Segment::C, Segment::E, Segment::F, Segment::G])));
+        assert_eq!(Glyph::from_char('h'), Some(Glyph::from_segments(&[Segment::C, Segment::E, Segment::F, Segment::G])));
+        assert_eq!(Glyph::from_char('L'), Glyph::from_char('l'));
+        assert_eq!(Glyph::from_char('O'), Glyph::from_digit(0));
+        assert_eq!(Glyph::from_char('-'), Some(Glyph::MINUS));
+        assert_eq!(Glyph::from_char('_'), Some(Glyph::UNDERSCORE));
+        assert_eq!(Glyph::from_char('°'), Some(Glyph::DEGREE));
+        assert_eq!(Glyph::from_char(' '), Some(Glyph::BLANK));
+    }
+
+    #[test]
+    fn unsupported_characters() {
+        for c in ['K', 'm', 'V', 'w', 'X', '.', '?', 'é', '\n'] {
+            assert_eq!(Glyph::from_char(c), None, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn with_dp_only_touches_the_decimal_point() {
+        let eight = Glyph::from_digit(8).unwrap();
+
+        let dotted = eight.with_dp(true);
+        assert_eq!(dotted.bits(), 0xFF);
+        assert!(dotted.contains(Segment::DP));
+        assert_eq!(dotted.with_dp(true), dotted);
+        assert_eq!(dotted.with_dp(false), eight);
+        assert_eq!(Glyph::BLANK.with_dp(true), Glyph::from_segments(&[Segment::DP]));
+    }
+}
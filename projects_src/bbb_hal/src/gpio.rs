@@ -0,0 +1,231 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    path::PathBuf,
+};
+
+use gpiocdev::{
+    line::{Value, Values},
+    request::Request,
+};
+
+pub mod chip;
+pub mod mock;
+
+use chip::ChipMap;
+
+/// Access to GPIO lines, addressed by their AM335x GPIO number (`bank * 32 + offset`).
+///
+/// The displays only talk to their lines through this trait, so the same
+/// driver code runs on the board ([`CdevBackend`]) and on a development
+/// machine ([`mock::MockBackend`]). Backends are `Send` so a display can be
+/// handed to a [`RefreshThread`](crate::refresh::RefreshThread).
+pub trait GpioBackend: Send {
+    /// Request GPIO pins as outputs.
+    ///
+    /// Values are logical: with `active_low` set, `Value::Active` drives the
+    /// line low, for all subsequent reads and writes of the line.
+    ///
+    /// # Arguments
+    /// * `pins` - The GPIO pin numbers.
+    /// * `initial` - The value the lines are driven to once requested.
+    /// * `active_low` - Whether the lines are active when driven low.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is unsupported or busy.
+    fn request_outputs(&mut self, pins: &[u32], initial: Value, active_low: bool) -> Result<(), Box<dyn Error>>;
+
+    /// Request GPIO pins as inputs, e.g. for buttons.
+    ///
+    /// # Arguments
+    /// * `pins` - The GPIO pin numbers.
+    /// * `active_low` - Whether the lines read `Value::Active` when low, e.g. for a button pulling the line to ground.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is unsupported or busy.
+    fn request_inputs(&mut self, pins: &[u32], active_low: bool) -> Result<(), Box<dyn Error>>;
+
+    /// Set the value of a requested GPIO pin.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    /// * `value` - The value to drive the line to ( `Value::Active` or `Value::Inactive`).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    fn set_line(&mut self, pin: u32, value: Value) -> Result<(), Box<dyn Error>>;
+
+    /// Set the values of several requested GPIO pins.
+    ///
+    /// Backends write the lines sharing a chip at once where the hardware
+    /// allows it, the default implementation sets them one by one.
+    ///
+    /// # Arguments
+    /// * `values` - The GPIO pin numbers and the values to drive them to.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized.
+    fn set_lines(&mut self, values: &[(u32, Value)]) -> Result<(), Box<dyn Error>> {
+
+        for &(pin, value) in values {
+            self.set_line(pin, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the value of a requested GPIO pin.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    ///
+    /// # Returns
+    /// * `Result<Value, Box<dyn Error>>` - The current value, or an error if the pin is not initialized.
+    fn get_line(&self, pin: u32) -> Result<Value, Box<dyn Error>>;
+}
+
+/// GPIO backend using the Linux GPIO character device through gpiocdev.
+///
+/// Chips are discovered by label the first time lines are requested, see [`ChipMap`].
+/// The lines of one [`GpioBackend::request_outputs`] call are bundled into a
+/// single request per chip, so [`GpioBackend::set_lines`] writes a whole
+/// frame with one ioctl per chip instead of one per line.
+///
+/// # Fields
+/// * `chips` - The GPIO chips of the system, once discovered.
+/// * `requests` - The multi-line requests, one per chip and `request_outputs` or `request_inputs` call.
+/// * `lines` - A HashMap containing the request index and chip offset of every line.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::{gpio::{CdevBackend, GpioBackend}, Value};
+///
+/// let mut lines = CdevBackend::new();
+/// lines.request_outputs(&[66, 67], Value::Inactive, false)?;
+/// lines.set_lines(&[(66, Value::Active), (67, Value::Active)])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct CdevBackend {
+    chips: Option<ChipMap>,
+    requests: Vec<Request>,
+    lines: HashMap<u32, (usize, u32)>,
+}
+
+impl CdevBackend {
+    /// Create a backend without any requested line.
+    ///
+    /// # Returns
+    /// * `CdevBackend` - A new instance of the CdevBackend struct.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a backend resolving its lines on the given chips, e.g. gpio-sim chips.
+    ///
+    /// # Arguments
+    /// * `chips` - The chips to request lines on.
+    ///
+    /// # Returns
+    /// * `CdevBackend` - A new instance of the CdevBackend struct.
+    pub fn with_chips(chips: ChipMap) -> Self {
+        Self { chips: Some(chips), ..Self::default() }
+    }
+
+    /// Request lines, bundled per chip.
+    ///
+    /// # Arguments
+    /// * `pins` - The GPIO pin numbers.
+    /// * `output` - The initial value of output lines, `None` for input lines.
+    /// * `active_low` - Whether the lines are active when low.
+    fn request(&mut self, pins: &[u32], output: Option<Value>, active_low: bool) -> Result<(), Box<dyn Error>> {
+
+        let chips = match self.chips.take() {
+            Some(chips) => chips,
+            None => ChipMap::discover()?,
+        };
+        let chips = self.chips.insert(chips);
+
+        let mut by_chip: BTreeMap<PathBuf, Vec<(u32, u32)>> = BTreeMap::new();
+        for &pin in pins {
+            let (chip_path, offset) = chips.locate(pin)?;
+            by_chip.entry(chip_path.to_path_buf()).or_default().push((pin, offset));
+        }
+
+        for (chip_path, lines) in by_chip {
+            let offsets = lines.iter().map(|&(_, offset)| offset).collect::<Vec<_>>();
+
+            let mut builder = Request::builder();
+            builder
+                .on_chip(chip_path)
+                .with_lines(&offsets);
+            match output {
+                Some(initial) => builder.as_output(initial),
+                None => builder.as_input(),
+            };
+            if active_low {
+                builder.as_active_low();
+            }
+            let req = builder.request()?;
+
+            let index = self.requests.len();
+            self.requests.push(req);
+            for (pin, offset) in lines {
+                self.lines.insert(pin, (index, offset));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn line(&self, pin: u32) -> Result<(&Request, u32), Box<dyn Error>> {
+        self.lines
+            .get(&pin)
+            .map(|&(index, offset)| (&self.requests[index], offset))
+            .ok_or_else(|| format!("Pin {} not initialized", pin).into())
+    }
+}
+
+impl GpioBackend for CdevBackend {
+    fn request_outputs(&mut self, pins: &[u32], initial: Value, active_low: bool) -> Result<(), Box<dyn Error>> {
+        self.request(pins, Some(initial), active_low)
+    }
+
+    fn request_inputs(&mut self, pins: &[u32], active_low: bool) -> Result<(), Box<dyn Error>> {
+        self.request(pins, None, active_low)
+    }
+
+    fn set_line(&mut self, pin: u32, value: Value) -> Result<(), Box<dyn Error>> {
+
+        let (req, offset) = self.line(pin)?;
+        req.set_value(offset, value)?;
+
+        Ok(())
+    }
+
+    fn set_lines(&mut self, values: &[(u32, Value)]) -> Result<(), Box<dyn Error>> {
+
+        let mut by_request: BTreeMap<usize, Values> = BTreeMap::new();
+        for &(pin, value) in values {
+            let &(index, offset) = self.lines
+                .get(&pin)
+                .ok_or_else(|| format!("Pin {} not initialized", pin))?;
+            by_request.entry(index).or_default().set(offset, value);
+        }
+
+        for (index, values) in by_request {
+            self.requests[index].set_values(&values)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_line(&self, pin: u32) -> Result<Value, Box<dyn Error>> {
+
+        let (req, offset) = self.line(pin)?;
+
+        Ok(req.value(offset)?)
+    }
+}
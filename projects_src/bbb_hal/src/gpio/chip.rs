@@ -0,0 +1,171 @@
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use gpiocdev::chip::Chip;
+
+/// Number of lines of every AM335x GPIO bank.
+pub const LINES_PER_BANK: u32 = 32;
+
+/// Labels the kernel gives to the AM335x GPIO banks, indexed by bank number.
+///
+/// Depending on the kernel version a bank is labelled either by its legacy
+/// GPIO range or by the address of its controller.
+const BANK_LABELS: [[&str; 2]; 4] = [
+    ["gpio-0-31", "44e07000.gpio"],
+    ["gpio-32-63", "4804c000.gpio"],
+    ["gpio-64-95", "481ac000.gpio"],
+    ["gpio-96-127", "481ae000.gpio"],
+];
+
+/// A GPIO chip found under `/dev`.
+///
+/// # Fields
+/// * `path` - The path of the chip character device.
+/// * `label` - The label the kernel gave to the chip.
+/// * `num_lines` - The number of lines provided by the chip.
+/// * `bank` - The AM335x GPIO bank the chip was identified as, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipEntry {
+    pub path: PathBuf,
+    pub label: String,
+    pub num_lines: u32,
+    pub bank: Option<u32>,
+}
+
+impl fmt::Display for ChipEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] ({} lines)", self.path.display(), self.label, self.num_lines)
+    }
+}
+
+/// The GPIO chips of the system, matched to the AM335x banks by label.
+///
+/// GPIO pins are addressed by their AM335x number `bank * 32 + offset`
+/// (e.g. `GPIO2_2` is pin 66), which does not depend on the order or the
+/// base the kernel enumerates the chips with.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::gpio::chip::ChipMap;
+///
+/// let chips = ChipMap::discover()?;
+/// let (chip_path, offset) = chips.locate(66)?;
+/// println!("GPIO2_2 is line {} of {}", offset, chip_path.display());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChipMap {
+    chips: Vec<ChipEntry>,
+}
+
+impl ChipMap {
+    /// Enumerate the GPIO chips under `/dev`.
+    ///
+    /// # Returns
+    /// * `Result<ChipMap, Box<dyn Error>>` - The chips found, or an error if `/dev` cannot be read.
+    pub fn discover() -> Result<Self, Box<dyn Error>> {
+
+        let mut chips = Vec::new();
+
+        for path in gpiocdev::chip::chips()? {
+            let info = Chip::from_path(&path)?.info()?;
+            let bank = bank_of_label(&info.label);
+
+            chips.push(ChipEntry {
+                path,
+                label: info.label,
+                num_lines: info.num_lines,
+                bank,
+            });
+        }
+
+        Ok(Self { chips })
+    }
+
+    /// The chips found during discovery.
+    pub fn chips(&self) -> &[ChipEntry] {
+        &self.chips
+    }
+
+    /// Get the chip path and offset for a given GPIO pin.
+    ///
+    /// # Arguments
+    /// * `pin` - The AM335x GPIO number (`bank * 32 + offset`).
+    ///
+    /// # Returns
+    /// * `Result<(&Path, u32), Box<dyn Error>>` - A tuple containing the chip path and offset.
+    pub fn locate(&self, pin: u32) -> Result<(&Path, u32), Box<dyn Error>> {
+
+        let (bank, offset) = (pin / LINES_PER_BANK, pin % LINES_PER_BANK);
+
+        self.chips
+            .iter()
+            .find(|chip| chip.bank == Some(bank) && offset < chip.num_lines)
+            .map(|chip| (chip.path.as_path(), offset))
+            .ok_or_else(|| {
+                format!(
+                    "GPIO {} (GPIO{}_{}) not found, expected a chip labelled {}; available chips: {}",
+                    pin,
+                    bank,
+                    offset,
+                    BANK_LABELS
+                        .get(bank as usize)
+                        .map(|labels| labels.join(" or "))
+                        .unwrap_or_else(|| "<no AM335x bank>".to_string()),
+                    self.describe(),
+                )
+                .into()
+            })
+    }
+
+    /// Get the GPIO pin of a line by the name given to it in the device tree.
+    ///
+    /// # Arguments
+    /// * `name` - The line name, e.g. `P8_07`.
+    ///
+    /// # Returns
+    /// * `Result<u32, Box<dyn Error>>` - The AM335x GPIO number of the line.
+    pub fn find_named(&self, name: &str) -> Result<u32, Box<dyn Error>> {
+
+        for chip in &self.chips {
+            let Some(bank) = chip.bank else { continue };
+
+            if let Some(info) = Chip::from_path(&chip.path)?.find_line_info(name) {
+                return Ok(bank * LINES_PER_BANK + info.offset);
+            }
+        }
+
+        Err(format!("No GPIO line named {:?}; available chips: {}", name, self.describe()).into())
+    }
+
+    fn describe(&self) -> String {
+        if self.chips.is_empty() {
+            return "none".to_string();
+        }
+
+        self.chips
+            .iter()
+            .map(ChipEntry::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Get the AM335x GPIO bank a chip label refers to.
+///
+/// # Arguments
+/// * `label` - The chip label, e.g. `gpio-64-95` or `481ac000.gpio`.
+///
+/// # Returns
+/// * `Option<u32>` - The bank number, or `None` for chips that are not an AM335x bank.
+pub fn bank_of_label(label: &str) -> Option<u32> {
+    BANK_LABELS
+        .iter()
+        .position(|labels| labels.contains(&label))
+        .map(|bank| bank as u32)
+}
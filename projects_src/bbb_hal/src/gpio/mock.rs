@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use gpiocdev::line::Value;
+
+use super::GpioBackend;
+
+#[derive(Default)]
+struct MockState {
+    levels: HashMap<u32, Value>,
+    active_low: HashMap<u32, bool>,
+    writes: Vec<(u32, Value)>,
+}
+
+/// In-memory GPIO backend recording every line write.
+///
+/// Clones share the same state, so a test can hand one clone to a display
+/// and keep another one to inspect what the display did.
+///
+/// # Examples
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::{gpio::{mock::MockBackend, GpioBackend}, Value};
+///
+/// let mock = MockBackend::new();
+/// let mut backend = mock.clone();
+/// backend.request_outputs(&[66, 67], Value::Inactive, true)?;
+/// backend.set_line(67, Value::Active)?;
+///
+/// assert_eq!(mock.writes(), vec![(67, Value::Active)]);
+/// assert_eq!(mock.level(66), Some(Value::Inactive));
+/// assert_eq!(mock.is_high(67), Some(false));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockBackend {
+    /// Create a mock without any requested line.
+    ///
+    /// # Returns
+    /// * `MockBackend` - A new instance of the MockBackend struct.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Every line write since the mock was created or last cleared, in order.
+    ///
+    /// Values are logical, see [`MockBackend::is_active_low`]. Values driven
+    /// when the lines are requested are not recorded.
+    ///
+    /// # Returns
+    /// * `Vec<(u32, Value)>` - The GPIO pin numbers and the values written to them.
+    pub fn writes(&self) -> Vec<(u32, Value)> {
+        self.state().writes.clone()
+    }
+
+    /// Forget the recorded line writes, keeping the current line levels.
+    pub fn clear_writes(&self) {
+        self.state().writes.clear();
+    }
+
+    /// The current logical value of a line.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    ///
+    /// # Returns
+    /// * `Option<Value>` - The value, or `None` if the pin was never requested.
+    pub fn level(&self, pin: u32) -> Option<Value> {
+        self.state().levels.get(&pin).copied()
+    }
+
+    /// Whether a line was requested as active-low.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    ///
+    /// # Returns
+    /// * `Option<bool>` - The flag, or `None` if the pin was never requested.
+    pub fn is_active_low(&self, pin: u32) -> Option<bool> {
+        self.state().active_low.get(&pin).copied()
+    }
+
+    /// The electrical level of a line, taking the active-low flag into account.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    ///
+    /// # Returns
+    /// * `Option<bool>` - `true` if the line is driven high, or `None` if the pin was never requested.
+    pub fn is_high(&self, pin: u32) -> Option<bool> {
+        let state = self.state();
+        let active = *state.levels.get(&pin)? == Value::Active;
+        Some(active != state.active_low.get(&pin).copied().unwrap_or(false))
+    }
+
+    /// Drive a line from the outside, e.g. to simulate a button press.
+    ///
+    /// # Arguments
+    /// * `pin` - The GPIO pin number.
+    /// * `value` - The value subsequent reads of the line return.
+    pub fn set_level(&self, pin: u32, value: Value) {
+        self.state().levels.insert(pin, value);
+    }
+}
+
+impl GpioBackend for MockBackend {
+    fn request_outputs(&mut self, pins: &[u32], initial: Value, active_low: bool) -> Result<(), Box<dyn Error>> {
+
+        let mut state = self.state();
+        for &pin in pins {
+            state.levels.insert(pin, initial);
+            state.active_low.insert(pin, active_low);
+        }
+
+        Ok(())
+    }
+
+    fn request_inputs(&mut self, pins: &[u32], active_low: bool) -> Result<(), Box<dyn Error>> {
+
+        let mut state = self.state();
+        for &pin in pins {
+            // keep a level already driven with set_level
+            state.levels.entry(pin).or_insert(Value::Inactive);
+            state.active_low.insert(pin, active_low);
+        }
+
+        Ok(())
+    }
+
+    fn set_line(&mut self, pin: u32, value: Value) -> Result<(), Box<dyn Error>> {
+
+        let mut state = self.state();
+        let level = state.levels
+            .get_mut(&pin)
+            .ok_or_else(|| format!("Pin {} not initialized", pin))?;
+        *level = value;
+        state.writes.push((pin, value));
+
+        Ok(())
+    }
+
+    fn set_lines(&mut self, values: &[(u32, Value)]) -> Result<(), Box<dyn Error>> {
+
+        let mut state = self.state();
+        if let Some(&(pin, _)) = values.iter().find(|(pin, _)| !state.levels.contains_key(pin)) {
+            return Err(format!("Pin {} not initialized", pin).into());
+        }
+
+        for &(pin, value) in values {
+            state.levels.insert(pin, value);
+            state.writes.push((pin, value));
+        }
+
+        Ok(())
+    }
+
+    fn get_line(&self, pin: u32) -> Result<Value, Box<dyn Error>> {
+        self.level(pin)
+            .ok_or_else(|| format!("Pin {} not initialized", pin).into())
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt,
+    str::FromStr,
+};
+
+use crate::gpio::chip::LINES_PER_BANK;
+
+/// Peripheral a header pin is claimed by in the default BeagleBone Black setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conflict {
+    /// Data, clock or command line of the on-board eMMC.
+    Emmc,
+    /// Video line of the on-board HDMI framer.
+    Hdmi,
+    /// McASP0 audio line of the on-board HDMI framer.
+    HdmiAudio,
+    /// I2C2 bus used by the cape manager to read the cape EEPROMs.
+    CapeI2c,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Conflict::Emmc => "eMMC",
+            Conflict::Hdmi => "HDMI",
+            Conflict::HdmiAudio => "HDMI audio",
+            Conflict::CapeI2c => "cape EEPROM I2C",
+        })
+    }
+}
+
+/// Static description of a header pin.
+///
+/// # Fields
+/// * `bank` - The AM335x GPIO bank of the pin.
+/// * `offset` - The line offset of the pin within its bank.
+/// * `mode0` - The name of the pin in pinmux mode 0.
+/// * `default_mode` - The pinmux mode the pin is in after boot, 7 being GPIO.
+/// * `conflict` - The on-board peripheral using the pin, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinInfo {
+    pub bank: u32,
+    pub offset: u32,
+    pub mode0: &'static str,
+    pub default_mode: u8,
+    pub conflict: Option<Conflict>,
+}
+
+/// A GPIO capable pin of the P8 and P9 expansion headers.
+///
+/// `P9_41` and `P9_42` are each wired to a second ball (`GPIO3_20` and
+/// `GPIO3_18`), which must be left as input when the pin is used as GPIO.
+///
+/// # Examples
+/// ```
+/// use bbb_hal::header::HeaderPin;
+///
+/// let pin: HeaderPin = "P8_07".parse().unwrap();
+/// assert_eq!(pin, HeaderPin::P8_7);
+/// assert_eq!(pin.gpio(), 66);
+/// assert_eq!(pin.to_string(), "P8_7");
+/// ```
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HeaderPin {
+    P8_3,
+    P8_4,
+    P8_5,
+    P8_6,
+    P8_7,
+    P8_8,
+    P8_9,
+    P8_10,
+    P8_11,
+    P8_12,
+    P8_13,
+    P8_14,
+    P8_15,
+    P8_16,
+    P8_17,
+    P8_18,
+    P8_19,
+    P8_20,
+    P8_21,
+    P8_22,
+    P8_23,
+    P8_24,
+    P8_25,
+    P8_26,
+    P8_27,
+    P8_28,
+    P8_29,
+    P8_30,
+    P8_31,
+    P8_32,
+    P8_33,
+    P8_34,
+    P8_35,
+    P8_36,
+    P8_37,
+    P8_38,
+    P8_39,
+    P8_40,
+    P8_41,
+    P8_42,
+    P8_43,
+    P8_44,
+    P8_45,
+    P8_46,
+    P9_11,
+    P9_12,
+    P9_13,
+    P9_14,
+    P9_15,
+    P9_16,
+    P9_17,
+    P9_18,
+    P9_19,
+    P9_20,
+    P9_21,
+    P9_22,
+    P9_23,
+    P9_24,
+    P9_25,
+    P9_26,
+    P9_27,
+    P9_28,
+    P9_29,
+    P9_30,
+    P9_31,
+    P9_41,
+    P9_42,
+}
+
+impl HeaderPin {
+    /// All GPIO capable header pins, P8 first.
+    pub const ALL: [HeaderPin; 67] = [
+        HeaderPin::P8_3,
+        HeaderPin::P8_4,
+        HeaderPin::P8_5,
+        HeaderPin::P8_6,
+        HeaderPin::P8_7,
+        HeaderPin::P8_8,
+        HeaderPin::P8_9,
+        HeaderPin::P8_10,
+        HeaderPin::P8_11,
+        HeaderPin::P8_12,
+        HeaderPin::P8_13,
+        HeaderPin::P8_14,
+        HeaderPin::P8_15,
+        HeaderPin::P8_16,
+        HeaderPin::P8_17,
+        HeaderPin::P8_18,
+        HeaderPin::P8_19,
+        HeaderPin::P8_20,
+        HeaderPin::P8_21,
+        HeaderPin::P8_22,
+        HeaderPin::P8_23,
+        HeaderPin::P8_24,
+        HeaderPin::P8_25,
+        HeaderPin::P8_26,
+        HeaderPin::P8_27,
+        HeaderPin::P8_28,
+        HeaderPin::P8_29,
+        HeaderPin::P8_30,
+        HeaderPin::P8_31,
+        HeaderPin::P8_32,
+        HeaderPin::P8_33,
+        HeaderPin::P8_34,
+        HeaderPin::P8_35,
+        HeaderPin::P8_36,
+        HeaderPin::P8_37,
+        HeaderPin::P8_38,
+        HeaderPin::P8_39,
+        HeaderPin::P8_40,
+        HeaderPin::P8_41,
+        HeaderPin::P8_42,
+        HeaderPin::P8_43,
+        HeaderPin::P8_44,
+        HeaderPin::P8_45,
+        HeaderPin::P8_46,
+        HeaderPin::P9_11,
+        HeaderPin::P9_12,
+        HeaderPin::P9_13,
+        HeaderPin::P9_14,
+        HeaderPin::P9_15,
+        HeaderPin::P9_16,
+        HeaderPin::P9_17,
+        HeaderPin::P9_18,
+        HeaderPin::P9_19,
+        HeaderPin::P9_20,
+        HeaderPin::P9_21,
+        HeaderPin::P9_22,
+        HeaderPin::P9_23,
+        HeaderPin::P9_24,
+        HeaderPin::P9_25,
+        HeaderPin::P9_26,
+        HeaderPin::P9_27,
+        HeaderPin::P9_28,
+        HeaderPin::P9_29,
+        HeaderPin::P9_30,
+        HeaderPin::P9_31,
+        HeaderPin::P9_41,
+        HeaderPin::P9_42,
+    ];
+
+    /// Get the static description of the pin.
+    ///
+    /// # Returns
+    /// * `PinInfo` - The bank, offset, modes and conflicts of the pin.
+    pub fn info(self) -> PinInfo {
+        match self {
+            HeaderPin::P8_3  => PinInfo { bank: 1, offset:  6, mode0: "gpmc_ad6", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_4  => PinInfo { bank: 1, offset:  7, mode0: "gpmc_ad7", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_5  => PinInfo { bank: 1, offset:  2, mode0: "gpmc_ad2", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_6  => PinInfo { bank: 1, offset:  3, mode0: "gpmc_ad3", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_7  => PinInfo { bank: 2, offset:  2, mode0: "gpmc_advn_ale", default_mode: 7, conflict: None },
+            HeaderPin::P8_8  => PinInfo { bank: 2, offset:  3, mode0: "gpmc_oen_ren", default_mode: 7, conflict: None },
+            HeaderPin::P8_9  => PinInfo { bank: 2, offset:  5, mode0: "gpmc_be0n_cle", default_mode: 7, conflict: None },
+            HeaderPin::P8_10 => PinInfo { bank: 2, offset:  4, mode0: "gpmc_wen", default_mode: 7, conflict: None },
+            HeaderPin::P8_11 => PinInfo { bank: 1, offset: 13, mode0: "gpmc_ad13", default_mode: 7, conflict: None },
+            HeaderPin::P8_12 => PinInfo { bank: 1, offset: 12, mode0: "gpmc_ad12", default_mode: 7, conflict: None },
+            HeaderPin::P8_13 => PinInfo { bank: 0, offset: 23, mode0: "gpmc_ad9", default_mode: 7, conflict: None },
+            HeaderPin::P8_14 => PinInfo { bank: 0, offset: 26, mode0: "gpmc_ad10", default_mode: 7, conflict: None },
+            HeaderPin::P8_15 => PinInfo { bank: 1, offset: 15, mode0: "gpmc_ad15", default_mode: 7, conflict: None },
+            HeaderPin::P8_16 => PinInfo { bank: 1, offset: 14, mode0: "gpmc_ad14", default_mode: 7, conflict: None },
+            HeaderPin::P8_17 => PinInfo { bank: 0, offset: 27, mode0: "gpmc_ad11", default_mode: 7, conflict: None },
+            HeaderPin::P8_18 => PinInfo { bank: 2, offset:  1, mode0: "gpmc_clk", default_mode: 7, conflict: None },
+            HeaderPin::P8_19 => PinInfo { bank: 0, offset: 22, mode0: "gpmc_ad8", default_mode: 7, conflict: None },
+            HeaderPin::P8_20 => PinInfo { bank: 1, offset: 31, mode0: "gpmc_csn2", default_mode: 2, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_21 => PinInfo { bank: 1, offset: 30, mode0: "gpmc_csn1", default_mode: 2, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_22 => PinInfo { bank: 1, offset:  5, mode0: "gpmc_ad5", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_23 => PinInfo { bank: 1, offset:  4, mode0: "gpmc_ad4", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_24 => PinInfo { bank: 1, offset:  1, mode0: "gpmc_ad1", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_25 => PinInfo { bank: 1, offset:  0, mode0: "gpmc_ad0", default_mode: 1, conflict: Some(Conflict::Emmc) },
+            HeaderPin::P8_26 => PinInfo { bank: 1, offset: 29, mode0: "gpmc_csn0", default_mode: 7, conflict: None },
+            HeaderPin::P8_27 => PinInfo { bank: 2, offset: 22, mode0: "lcd_vsync", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_28 => PinInfo { bank: 2, offset: 24, mode0: "lcd_pclk", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_29 => PinInfo { bank: 2, offset: 23, mode0: "lcd_hsync", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_30 => PinInfo { bank: 2, offset: 25, mode0: "lcd_ac_bias_en", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_31 => PinInfo { bank: 0, offset: 10, mode0: "lcd_data14", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_32 => PinInfo { bank: 0, offset: 11, mode0: "lcd_data15", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_33 => PinInfo { bank: 0, offset:  9, mode0: "lcd_data13", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_34 => PinInfo { bank: 2, offset: 17, mode0: "lcd_data11", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_35 => PinInfo { bank: 0, offset:  8, mode0: "lcd_data12", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_36 => PinInfo { bank: 2, offset: 16, mode0: "lcd_data10", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_37 => PinInfo { bank: 2, offset: 14, mode0: "lcd_data8", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_38 => PinInfo { bank: 2, offset: 15, mode0: "lcd_data9", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_39 => PinInfo { bank: 2, offset: 12, mode0: "lcd_data6", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_40 => PinInfo { bank: 2, offset: 13, mode0: "lcd_data7", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_41 => PinInfo { bank: 2, offset: 10, mode0: "lcd_data4", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_42 => PinInfo { bank: 2, offset: 11, mode0: "lcd_data5", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_43 => PinInfo { bank: 2, offset:  8, mode0: "lcd_data2", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_44 => PinInfo { bank: 2, offset:  9, mode0: "lcd_data3", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_45 => PinInfo { bank: 2, offset:  6, mode0: "lcd_data0", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P8_46 => PinInfo { bank: 2, offset:  7, mode0: "lcd_data1", default_mode: 0, conflict: Some(Conflict::Hdmi) },
+            HeaderPin::P9_11 => PinInfo { bank: 0, offset: 30, mode0: "gpmc_wait0", default_mode: 7, conflict: None },
+            HeaderPin::P9_12 => PinInfo { bank: 1, offset: 28, mode0: "gpmc_be1n", default_mode: 7, conflict: None },
+            HeaderPin::P9_13 => PinInfo { bank: 0, offset: 31, mode0: "gpmc_wpn", default_mode: 7, conflict: None },
+            HeaderPin::P9_14 => PinInfo { bank: 1, offset: 18, mode0: "gpmc_a2", default_mode: 7, conflict: None },
+            HeaderPin::P9_15 => PinInfo { bank: 1, offset: 16, mode0: "gpmc_a0", default_mode: 7, conflict: None },
+            HeaderPin::P9_16 => PinInfo { bank: 1, offset: 19, mode0: "gpmc_a3", default_mode: 7, conflict: None },
+            HeaderPin::P9_17 => PinInfo { bank: 0, offset:  5, mode0: "spi0_cs0", default_mode: 7, conflict: None },
+            HeaderPin::P9_18 => PinInfo { bank: 0, offset:  4, mode0: "spi0_d1", default_mode: 7, conflict: None },
+            HeaderPin::P9_19 => PinInfo { bank: 0, offset: 13, mode0: "uart1_rtsn", default_mode: 3, conflict: Some(Conflict::CapeI2c) },
+            HeaderPin::P9_20 => PinInfo { bank: 0, offset: 12, mode0: "uart1_ctsn", default_mode: 3, conflict: Some(Conflict::CapeI2c) },
+            HeaderPin::P9_21 => PinInfo { bank: 0, offset:  3, mode0: "spi0_d0", default_mode: 7, conflict: None },
+            HeaderPin::P9_22 => PinInfo { bank: 0, offset:  2, mode0: "spi0_sclk", default_mode: 7, conflict: None },
+            HeaderPin::P9_23 => PinInfo { bank: 1, offset: 17, mode0: "gpmc_a1", default_mode: 7, conflict: None },
+            HeaderPin::P9_24 => PinInfo { bank: 0, offset: 15, mode0: "uart1_txd", default_mode: 7, conflict: None },
+            HeaderPin::P9_25 => PinInfo { bank: 3, offset: 21, mode0: "mcasp0_ahclkx", default_mode: 0, conflict: Some(Conflict::HdmiAudio) },
+            HeaderPin::P9_26 => PinInfo { bank: 0, offset: 14, mode0: "uart1_rxd", default_mode: 7, conflict: None },
+            HeaderPin::P9_27 => PinInfo { bank: 3, offset: 19, mode0: "mcasp0_fsr", default_mode: 7, conflict: None },
+            HeaderPin::P9_28 => PinInfo { bank: 3, offset: 17, mode0: "mcasp0_ahclkr", default_mode: 0, conflict: Some(Conflict::HdmiAudio) },
+            HeaderPin::P9_29 => PinInfo { bank: 3, offset: 15, mode0: "mcasp0_fsx", default_mode: 0, conflict: Some(Conflict::HdmiAudio) },
+            HeaderPin::P9_30 => PinInfo { bank: 3, offset: 16, mode0: "mcasp0_axr0", default_mode: 7, conflict: None },
+            HeaderPin::P9_31 => PinInfo { bank: 3, offset: 14, mode0: "mcasp0_aclkx", default_mode: 0, conflict: Some(Conflict::HdmiAudio) },
+            HeaderPin::P9_41 => PinInfo { bank: 0, offset: 20, mode0: "xdma_event_intr1", default_mode: 7, conflict: None },
+            HeaderPin::P9_42 => PinInfo { bank: 0, offset:  7, mode0: "ecap0_in_pwm0_out", default_mode: 7, conflict: None },
+        }
+    }
+
+    /// Get the AM335x GPIO number of the pin.
+    ///
+    /// # Returns
+    /// * `u32` - The GPIO number (`bank * 32 + offset`).
+    pub fn gpio(self) -> u32 {
+        let info = self.info();
+        info.bank * LINES_PER_BANK + info.offset
+    }
+
+    /// Get the header pin wired to an AM335x GPIO number.
+    ///
+    /// # Arguments
+    /// * `gpio` - The GPIO number (`bank * 32 + offset`).
+    ///
+    /// # Returns
+    /// * `Option<HeaderPin>` - The header pin, or `None` if the GPIO is not on a header.
+    pub fn from_gpio(gpio: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|pin| pin.gpio() == gpio)
+    }
+
+    /// Check that the pin is not claimed by an on-board peripheral.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error naming the conflicting peripheral.
+    pub fn check_free(self) -> Result<(), Box<dyn Error>> {
+        match self.info().conflict {
+            Some(conflict) => Err(format!("Pin {} is used by the on-board {}", self, conflict).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for HeaderPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for HeaderPin {
+    type Err = Box<dyn Error>;
+
+    /// Parse a pin name such as `P8_7`, `P8_07` or `p9_12`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let invalid = || format!("Invalid header pin: {} (expected a GPIO capable pin such as P8_7)", s);
+
+        let (header, number) = s
+            .trim()
+            .split_once('_')
+            .ok_or_else(invalid)?;
+        let number: u32 = number.parse().map_err(|_| invalid())?;
+        let name = format!("{}_{}", header.to_ascii_uppercase(), number);
+
+        Self::ALL
+            .into_iter()
+            .find(|pin| pin.to_string() == name)
+            .ok_or_else(|| invalid().into())
+    }
+}
+
+/// Validate a set of header pins before requesting them.
+///
+/// # Arguments
+/// * `pins` - The pins a driver is about to use.
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if a pin is used twice or claimed by an on-board peripheral.
+pub fn validate(pins: &[HeaderPin]) -> Result<(), Box<dyn Error>> {
+
+    let mut seen = HashSet::new();
+
+    for &pin in pins {
+        if !seen.insert(pin) {
+            return Err(format!("Pin {} is assigned more than once", pin).into());
+        }
+        pin.check_free()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory the kernel exposes the LEDs in.
+pub const SYSFS_ROOT: &str = "/sys/class/leds";
+
+/// One of the four user LEDs next to the Ethernet jack, `USR0` to `USR3`.
+///
+/// The kernel trigger of the LED (heartbeat, mmc activity, ...) is turned
+/// off while the LED is held and restored when it is dropped.
+///
+/// # Fields
+/// * `path` - The sysfs directory of the LED.
+/// * `trigger` - The trigger active before the LED was opened.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::led::UserLed;
+///
+/// let mut led = UserLed::open(3)?;
+/// led.set(true)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UserLed {
+    path: PathBuf,
+    trigger: String,
+}
+
+impl UserLed {
+    /// Take control of a user LED under [`SYSFS_ROOT`].
+    ///
+    /// # Arguments
+    /// * `number` - The LED number, from 0 to 3.
+    ///
+    /// # Returns
+    /// * `Result<UserLed, Box<dyn Error>>` - The LED, turned off, or an error if it cannot be accessed.
+    pub fn open(number: u8) -> Result<Self, Box<dyn Error>> {
+        Self::open_at(SYSFS_ROOT, number)
+    }
+
+    /// Take control of a user LED under another sysfs root, e.g. a fake tree in tests.
+    ///
+    /// # Arguments
+    /// * `root` - The directory containing the LED directories.
+    /// * `number` - The LED number, from 0 to 3.
+    ///
+    /// # Returns
+    /// * `Result<UserLed, Box<dyn Error>>` - The LED, turned off, or an error if it cannot be accessed.
+    pub fn open_at<P: AsRef<Path>>(root: P, number: u8) -> Result<Self, Box<dyn Error>> {
+
+        if number > 3 {
+            return Err(format!("User LED {} does not exist, expected 0 to 3", number).into());
+        }
+
+        let path = root.as_ref().join(format!("beaglebone:green:usr{}", number));
+        let triggers = fs::read_to_string(path.join("trigger"))
+            .map_err(|e| format!("Cannot read trigger of {}: {}", path.display(), e))?;
+
+        // the active trigger is the one in brackets, e.g. "none [heartbeat] timer"
+        let trigger = triggers
+            .split_whitespace()
+            .find_map(|t| t.strip_prefix('[').and_then(|t| t.strip_suffix(']')))
+            .unwrap_or("none")
+            .to_string();
+
+        let mut led = Self { path, trigger };
+        led.write("trigger", "none")?;
+        led.set(false)?;
+
+        Ok(led)
+    }
+
+    /// Turn the LED on or off.
+    ///
+    /// # Arguments
+    /// * `on` - Whether the LED is lit.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the brightness cannot be written.
+    pub fn set(&mut self, on: bool) -> Result<(), Box<dyn Error>> {
+        self.write("brightness", if on { "1" } else { "0" })
+    }
+
+    fn write(&self, attr: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let path = self.path.join(attr);
+        fs::write(&path, value)
+            .map_err(|e| format!("Cannot write {} to {}: {}", value, path.display(), e).into())
+    }
+}
+
+/// Drop implementation to give the LED back to its kernel trigger.
+impl Drop for UserLed {
+    fn drop(&mut self) {
+        let _ = self.set(false);
+        let trigger = self.trigger.clone();
+        let _ = self.write("trigger", &trigger);
+    }
+}
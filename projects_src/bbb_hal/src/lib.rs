@@ -0,0 +1,33 @@
+//! Hardware abstraction shared by the BeagleBone Black seven-segment projects.
+//!
+//! The crate owns the GPIO line management ([`gpio`]), the P8/P9 header
+//! pin table ([`header`]), the segment font ([`font`]), the single-digit
+//! seven-segment driver ([`seven_segment`]), the 4-digit multiplexed driver
+//! ([`multiplex`]) with its number and text rendering ([`text`]) and
+//! background scanning ([`refresh`]), optionally with real-time scheduling
+//! ([`realtime`]), the brightness level of both ([`brightness`]), optionally
+//! through a hardware PWM channel ([`pwm`]), and their runtime pin
+//! assignment ([`wiring`]), so every binary drives the displays the same way
+//! and blanks them on a clean [`shutdown`]. The on-board user LEDs are
+//! driven through [`led`], the synchronization status of the system clock
+//! is read from [`system_clock`] and a DS3231 battery-backed clock through
+//! [`rtc`], with the date arithmetic of both in [`calendar`].
+
+pub mod brightness;
+pub mod calendar;
+pub mod font;
+pub mod gpio;
+pub mod header;
+pub mod led;
+pub mod multiplex;
+pub mod pwm;
+pub mod realtime;
+pub mod refresh;
+pub mod rtc;
+pub mod seven_segment;
+pub mod shutdown;
+pub mod system_clock;
+pub mod text;
+pub mod wiring;
+
+pub use gpiocdev::line::Value;
@@ -0,0 +1,566 @@
+use std::{
+    error::Error,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use gpiocdev::line::Value;
+
+use crate::{
+    brightness::Brightness,
+    font::Glyph,
+    gpio::{CdevBackend, GpioBackend},
+    seven_segment::{Segment, SevenSegmentDisplay},
+    text,
+    wiring::Wiring,
+};
+
+/// A digit position of the 4-digit display, from left to right.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Digits {
+    D_1,
+    D_2,
+    D_3,
+    D_4,
+}
+
+impl Digits {
+    /// All digit positions, from left to right.
+    pub const ALL: [Digits; DIGITS] = [Digits::D_1, Digits::D_2, Digits::D_3, Digits::D_4];
+}
+
+/// Number of digit positions of the display.
+pub const DIGITS: usize = 4;
+
+/// Index of the digit whose decimal point separates hours and minutes, or
+/// minutes and seconds, as in `12.34`.
+pub const SEPARATOR_DIGIT: usize = 1;
+
+/// The glyphs shown on the display, from left to right.
+pub type Frame = [Glyph; DIGITS];
+
+/// Timing of one digit in a multiplex scan.
+///
+/// Every digit is shown for `blanking + dwell`, so a full scan over the
+/// display takes four times as long.
+///
+/// # Fields
+/// * `dwell` - The time a digit stays lit with its segments.
+/// * `blanking` - The time all segments and digits stay off before switching
+///   to the next digit, letting the lines settle so neighbours do not ghost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScanTiming {
+    pub dwell: Duration,
+    pub blanking: Duration,
+}
+
+impl Default for ScanTiming {
+    fn default() -> Self {
+        Self {
+            dwell: Duration::from_micros(2400),
+            blanking: Duration::from_micros(100),
+        }
+    }
+}
+
+impl ScanTiming {
+    /// Derive the dwell time from a refresh rate, keeping the default blanking time.
+    ///
+    /// # Arguments
+    /// * `rate_hz` - The count of full scans over the four digits per second.
+    ///
+    /// # Returns
+    /// * `Result<ScanTiming, Box<dyn Error>>` - The timing, or an error if the rate is zero
+    ///   or too high to leave any dwell time after blanking.
+    pub fn from_rate(rate_hz: u32) -> Result<Self, Box<dyn Error>> {
+
+        if rate_hz == 0 {
+            return Err("Refresh rate must be at least 1 Hz".into());
+        }
+
+        let too_high = || format!("Refresh rate {} Hz leaves no time to light the digits", rate_hz);
+
+        let blanking = Self::default().blanking;
+        let slots = rate_hz.checked_mul(DIGITS as u32).ok_or_else(too_high)?;
+        let dwell = (Duration::from_secs(1) / slots)
+            .checked_sub(blanking)
+            .filter(|dwell| !dwell.is_zero())
+            .ok_or_else(too_high)?;
+
+        Ok(Self { dwell, blanking })
+    }
+
+    /// Get the time of a full scan over the display.
+    pub fn period(&self) -> Duration {
+        (self.dwell + self.blanking) * DIGITS as u32
+    }
+}
+
+/// Shared frame of a multiplexed display.
+///
+/// Clones share the same frame, so any thread can update what a
+/// [`RefreshThread`](crate::refresh::RefreshThread) shows.
+///
+/// # Fields
+/// * `frame` - The glyphs to show, from left to right.
+#[derive(Debug, Clone, Default)]
+pub struct FrameBuffer {
+    frame: Arc<Mutex<Frame>>,
+}
+
+impl FrameBuffer {
+    fn lock(&self) -> MutexGuard<'_, Frame> {
+        self.frame.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the current frame.
+    pub fn get(&self) -> Frame {
+        *self.lock()
+    }
+
+    /// Replace the frame.
+    ///
+    /// # Arguments
+    /// * `frame` - The glyphs to show, from left to right.
+    pub fn set(&self, frame: Frame) {
+        *self.lock() = frame;
+    }
+
+    /// Show an integer, right-aligned, or `----` if it does not fit.
+    ///
+    /// # Arguments
+    /// * `value` - The number to show, from -999 to 9999.
+    pub fn show_number(&self, value: i32) {
+        self.set(text::number(value));
+    }
+
+    /// Show a number with a fixed count of decimals, right-aligned, or `----` if it does not fit.
+    ///
+    /// # Arguments
+    /// * `value` - The number to show.
+    /// * `decimals` - The count of digits after the decimal point, up to 3.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if there are too many decimals.
+    pub fn show_fixed(&self, value: f32, decimals: u8) -> Result<(), Box<dyn Error>> {
+        self.set(text::fixed(value, decimals)?);
+        Ok(())
+    }
+
+    /// Show a text, left-aligned; a `.` lights the decimal point of the preceding character.
+    ///
+    /// # Arguments
+    /// * `text` - The text to show.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a character cannot be shown or the text is too long.
+    pub fn show_str(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        self.set(text::string(text)?);
+        Ok(())
+    }
+}
+
+/// A struct representing a 4-digit multiplexed seven-segment display.
+///
+/// The segment lines are shared by all digits, the digit lines select which
+/// position the segments are currently shown on.
+///
+/// The `show_*` methods render into a frame buffer, which [`MultiplexedDisplay::refresh`]
+/// scans onto the digits, or a [`RefreshThread`](crate::refresh::RefreshThread) in the background.
+///
+/// # Fields
+/// * `segments` - The seven-segment display driving the shared segment lines
+///   and owning the GPIO backend the digit lines are requested on.
+/// * `frame` - The glyphs to show on the next scans, shared with the [`FrameBuffer`] handles.
+/// * `brightness` - The part of the dwell time the digits are lit, shared with the [`Brightness`] handles.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::multiplex::{MultiplexedDisplay, ScanTiming};
+///
+/// let mut display = MultiplexedDisplay::new()?;
+/// display.show_fixed(-1.25, 2)?;
+/// loop {
+///     display.refresh(ScanTiming::default())?;
+/// }
+/// # }
+/// ```
+pub struct MultiplexedDisplay {
+    segments: SevenSegmentDisplay,
+    frame: FrameBuffer,
+    brightness: Brightness,
+}
+
+impl MultiplexedDisplay {
+    /// Create a new instance of the MultiplexedDisplay struct.
+    ///
+    /// # Returns
+    /// * `MultiplexedDisplay` - A new instance using the default wiring.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Self::with_wiring(Wiring::default())
+    }
+
+    /// Create a display with the given wiring.
+    ///
+    /// # Arguments
+    /// * `wiring` - The header pins and polarity of the segments and digits.
+    ///
+    /// # Returns
+    /// * `MultiplexedDisplay` - A new instance with segments inactive and all digits disabled.
+    pub fn with_wiring(wiring: Wiring) -> Result<Self, Box<dyn Error>> {
+        Self::with_backend(Box::new(CdevBackend::new()), wiring)
+    }
+
+    /// Create a display driving its segment and digit lines through the given GPIO backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The GPIO backend, e.g. a `MockBackend` in tests.
+    /// * `wiring` - The header pins and polarity of the segments and digits.
+    ///
+    /// # Returns
+    /// * `MultiplexedDisplay` - A new instance with segments inactive and all digits disabled.
+    pub fn with_backend(backend: Box<dyn GpioBackend>, wiring: Wiring) -> Result<Self, Box<dyn Error>> {
+
+        wiring.validate()?;
+
+        let polarity = wiring.digit_polarity;
+        let gpios = wiring.digit_pins().iter().map(|pin| pin.gpio()).collect::<Vec<_>>();
+        let mut segments = SevenSegmentDisplay::with_backend(backend, wiring)?;
+
+        // digits disabled
+        segments.backend_mut().request_outputs(&gpios, Value::Inactive, polarity.is_active_low())?;
+
+        Ok(Self { segments, frame: FrameBuffer::default(), brightness: Brightness::default() })
+    }
+
+    /// this function enables the digit specified by the parameter.
+    ///
+    /// # Arguments
+    /// * `target` - The digit to enable (Digit enum).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    pub fn enable_digit(&mut self, target: Digits) -> Result<(), Box<dyn Error>> {
+
+        let wiring = self.segments.wiring();
+        let lines = Digits::ALL.map(|dig| {
+            let val = if dig == target { Value::Active } else { Value::Inactive };
+            (wiring.digit(dig).gpio(), val)
+        });
+
+        let pin = wiring.digit(target);
+
+        self.segments
+            .backend_mut()
+            .set_lines(&lines)
+            .map_err(|e| format!("Digit {:?} ({}): {}", target, pin, e).into())
+    }
+
+    /// Turn all segments off and disable all digits, in one write.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized.
+    pub fn blank(&mut self) -> Result<(), Box<dyn Error>> {
+
+        let wiring = self.segments.wiring();
+        let lines = wiring.segment_pins()
+            .into_iter()
+            .chain(wiring.digit_pins())
+            .map(|pin| (pin.gpio(), Value::Inactive))
+            .collect::<Vec<_>>();
+
+        self.segments.backend_mut().set_lines(&lines)
+    }
+
+    /// Set the value of a segment on the currently enabled digit.
+    ///
+    /// # Arguments
+    /// * `seg` - The segment to set.
+    /// * `value` - The value to set the segment to ( `Value::Active` or `Value::Inactive`).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    pub fn set_segment(&mut self, seg: Segment, value: Value) -> Result<(), Box<dyn Error>> {
+        self.segments.set_segment(seg, value)
+    }
+
+    /// Clear all segments on the display.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    pub fn clear_all(&mut self) -> Result<(), Box<dyn Error>> {
+        self.segments.clear_all()
+    }
+
+    /// Set a digit on the currently enabled position.
+    ///
+    /// # Arguments
+    /// * `digit` - The digit to set. Must be between 0 and 15 (inclusive), shown in hexadecimal.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the digit is out of range or the pin is not initialized.
+    pub fn set_digit(&mut self, digit: u8) -> Result<(), Box<dyn Error>> {
+        self.segments.set_digit(digit)
+    }
+
+    /// Set a character on the currently enabled position.
+    ///
+    /// # Arguments
+    /// * `c` - The character to set, see [`Glyph::from_char`] for the supported ones.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the character has no glyph or the pin is not initialized.
+    pub fn set_char(&mut self, c: char) -> Result<(), Box<dyn Error>> {
+        self.segments.set_char(c)
+    }
+
+    /// Show a glyph on the currently enabled position.
+    ///
+    /// # Arguments
+    /// * `glyph` - The segments to light, decimal point included.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized.
+    pub fn set_glyph(&mut self, glyph: Glyph) -> Result<(), Box<dyn Error>> {
+        self.segments.set_glyph(glyph)
+    }
+
+    /// Sets the value of decimal point segment on the currently enabled position.
+    ///
+    /// # Arguments
+    /// * `state` - The value to set the decimal point segment.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - Result of the operation.
+    pub fn set_decimal_point(&mut self, state: bool) -> Result<(), Box<dyn Error>> {
+        self.segments.set_decimal_point(state)
+    }
+
+    /// Get a handle on the frame buffer, to update it from another thread.
+    pub fn buffer(&self) -> FrameBuffer {
+        self.frame.clone()
+    }
+
+    /// Get the frame buffer.
+    pub fn frame(&self) -> Frame {
+        self.frame.get()
+    }
+
+    /// Replace the frame buffer.
+    ///
+    /// # Arguments
+    /// * `frame` - The glyphs to show, from left to right.
+    pub fn set_frame(&mut self, frame: Frame) {
+        self.frame.set(frame);
+    }
+
+    /// Show an integer, see [`FrameBuffer::show_number`].
+    pub fn show_number(&mut self, value: i32) {
+        self.frame.show_number(value);
+    }
+
+    /// Show a number with a fixed count of decimals, see [`FrameBuffer::show_fixed`].
+    pub fn show_fixed(&mut self, value: f32, decimals: u8) -> Result<(), Box<dyn Error>> {
+        self.frame.show_fixed(value, decimals)
+    }
+
+    /// Show a text, see [`FrameBuffer::show_str`].
+    pub fn show_str(&mut self, text: &str) -> Result<(), Box<dyn Error>> {
+        self.frame.show_str(text)
+    }
+
+    /// Get a handle on the brightness level, to change it from another thread.
+    pub fn brightness(&self) -> Brightness {
+        self.brightness.clone()
+    }
+
+    /// Dim the display, taking effect on the next scan.
+    ///
+    /// # Arguments
+    /// * `level` - The brightness level, from 0 (off) to [`MAX_BRIGHTNESS`](crate::brightness::MAX_BRIGHTNESS).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the level is out of range.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn Error>> {
+        self.brightness.set(level)
+    }
+
+    /// Scan the frame buffer once over the four digits, sleeping with [`thread::sleep`].
+    ///
+    /// Every digit is blanked, switched to, written with its glyph (decimal
+    /// point included) and then left lit, so no segment of one digit ever
+    /// shows on its neighbour. Below full brightness, the digit is blanked
+    /// again for the rest of its dwell time. The display is blanked again
+    /// after the last digit.
+    ///
+    /// # Arguments
+    /// * `timing` - The blanking and dwell time of every digit.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized.
+    pub fn refresh(&mut self, timing: ScanTiming) -> Result<(), Box<dyn Error>> {
+        self.refresh_with(timing, |time| {
+            thread::sleep(time);
+            Ok(())
+        })
+    }
+
+    /// Scan the frame buffer once over the four digits, see [`MultiplexedDisplay::refresh`].
+    ///
+    /// # Arguments
+    /// * `timing` - The blanking and dwell time of every digit.
+    /// * `wait` - Called with the time every step of the scan lasts, e.g.
+    ///   [`DeadlineTimer::wait`](crate::realtime::DeadlineTimer::wait).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized or the wait failed.
+    pub fn refresh_with<F>(&mut self, timing: ScanTiming, mut wait: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(Duration) -> Result<(), Box<dyn Error>>,
+    {
+
+        let on = self.brightness.scale(timing.dwell);
+
+        for (dig, glyph) in Digits::ALL.into_iter().zip(self.frame.get()) {
+            self.blank()?;
+            wait(timing.blanking)?;
+            if !on.is_zero() {
+                self.enable_digit(dig)?;
+                self.segments.set_glyph(glyph)?;
+                wait(on)?;
+            }
+            if on < timing.dwell {
+                self.blank()?;
+                wait(timing.dwell - on)?;
+            }
+        }
+
+        self.blank()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpio::mock::MockBackend;
+    use crate::wiring::DisplayType;
+
+    #[test]
+    fn from_rate_splits_the_period() {
+        let timing = ScanTiming::from_rate(100).unwrap();
+        assert_eq!(timing.period(), Duration::from_millis(10));
+        assert_eq!(timing.blanking, ScanTiming::default().blanking);
+    }
+
+    #[test]
+    fn from_rate_rejects_zero_and_too_high_rates() {
+        for rate in [0, 1_000_000, u32::MAX / 2, u32::MAX] {
+            assert!(ScanTiming::from_rate(rate).is_err(), "{} Hz", rate);
+        }
+    }
+
+    /// The digits enabled by a scan and the glyph written right after each one.
+    fn scanned(writes: &[(u32, Value)], wiring: &Wiring) -> Vec<(usize, Glyph)> {
+        let digits = Digits::ALL.map(|dig| wiring.digit(dig).gpio());
+        let segments = Segment::ALL.map(|seg| wiring.segment(seg).gpio());
+
+        let mut shown = Vec::new();
+        for window in writes.windows(DIGITS + 8) {
+            let (enable, glyph) = window.split_at(DIGITS);
+            let active = enable.iter().filter(|(_, value)| *value == Value::Active).count();
+            if enable.iter().map(|(pin, _)| *pin).ne(digits) || active != 1 || glyph.iter().map(|(pin, _)| *pin).ne(segments) {
+                continue;
+            }
+
+            let digit = enable.iter().position(|(_, value)| *value == Value::Active).unwrap();
+            let glyph = Segment::ALL
+                .into_iter()
+                .zip(glyph)
+                .fold(Glyph::BLANK, |glyph, (seg, (_, value))| glyph.with_segment(seg, *value == Value::Active));
+            shown.push((digit, glyph));
+        }
+        shown
+    }
+
+    fn display() -> (MultiplexedDisplay, MockBackend) {
+        let mock = MockBackend::new();
+        let display = MultiplexedDisplay::with_backend(Box::new(mock.clone()), Wiring::default()).unwrap();
+        (display, mock)
+    }
+
+    #[test]
+    fn refresh_enables_every_digit_with_its_glyph() {
+        let (mut display, mock) = display();
+        display.show_str("12.34").unwrap();
+
+        let mut waits = Vec::new();
+        display.refresh_with(ScanTiming::default(), |wait| {
+            waits.push(wait);
+            Ok(())
+        }).unwrap();
+
+        let frame = text::string("12.34").unwrap();
+        assert_eq!(scanned(&mock.writes(), &Wiring::default()), frame.into_iter().enumerate().collect::<Vec<_>>());
+        // blanking then dwell for every digit at full brightness
+        let timing = ScanTiming::default();
+        assert_eq!(waits, [timing.blanking, timing.dwell].repeat(DIGITS));
+    }
+
+    #[test]
+    fn common_anode_digits_are_driven_high() {
+        let mock = MockBackend::new();
+        let mut wiring = Wiring::default();
+        wiring.set_display_type(DisplayType::CommonAnode);
+        let mut display = MultiplexedDisplay::with_backend(Box::new(mock.clone()), wiring.clone()).unwrap();
+
+        for dig in Digits::ALL {
+            let gpio = wiring.digit(dig).gpio();
+            assert_eq!(mock.is_active_low(gpio), Some(false), "{:?}", dig);
+            assert_eq!(mock.is_high(gpio), Some(false), "{:?}", dig);
+        }
+        for seg in Segment::ALL {
+            assert_eq!(mock.is_active_low(wiring.segment(seg).gpio()), Some(true), "{:?}", seg);
+        }
+
+        display.enable_digit(Digits::D_2).unwrap();
+        display.set_digit(7).unwrap();
+
+        // only the common anode of D_2 high, the lit segments pulled low
+        for dig in Digits::ALL {
+            assert_eq!(mock.is_high(wiring.digit(dig).gpio()), Some(dig == Digits::D_2), "{:?}", dig);
+        }
+        let seven = Glyph::from_digit(7).unwrap();
+        for seg in Segment::ALL {
+            assert_eq!(mock.is_high(wiring.segment(seg).gpio()), Some(!seven.contains(seg)), "{:?}", seg);
+        }
+
+        display.blank().unwrap();
+        assert!(Digits::ALL.iter().all(|dig| mock.is_high(wiring.digit(*dig).gpio()) == Some(false)));
+        assert!(Segment::ALL.iter().all(|seg| mock.is_high(wiring.segment(*seg).gpio()) == Some(true)));
+    }
+
+    #[test]
+    fn refresh_ends_blank() {
+        let (mut display, mock) = display();
+        display.show_number(8888);
+
+        display.refresh_with(ScanTiming::default(), |_| Ok(())).unwrap();
+
+        let wiring = Wiring::default();
+        assert!(wiring.segment_pins().iter().chain(wiring.digit_pins().iter()).all(|pin| mock.level(pin.gpio()) == Some(Value::Inactive)));
+    }
+
+    #[test]
+    fn dimmed_refresh_blanks_for_the_rest_of_the_dwell() {
+        let (mut display, mock) = display();
+        display.show_number(1);
+        display.set_brightness(0).unwrap();
+
+        display.refresh_with(ScanTiming::default(), |_| Ok(())).unwrap();
+
+        assert!(scanned(&mock.writes(), &Wiring::default()).is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::brightness;
+
+/// Directory the kernel exposes the PWM chips in.
+pub const SYSFS_ROOT: &str = "/sys/class/pwm";
+
+/// Time the kernel and udev get to create a channel directory after it is exported.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Level the output is driven to during the duty cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PwmPolarity {
+    /// The output is high during the duty cycle.
+    Normal,
+    /// The output is low during the duty cycle.
+    Inversed,
+}
+
+impl fmt::Display for PwmPolarity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PwmPolarity::Normal => write!(f, "normal"),
+            PwmPolarity::Inversed => write!(f, "inversed"),
+        }
+    }
+}
+
+/// A channel of a PWM chip, driven through the sysfs interface.
+///
+/// On the AM335x every eHRPWM module is a chip with two channels (`A` and
+/// `B` outputs), e.g. `48302200.pwm` for EHRPWM1 on P9_14/P9_16. The pin
+/// has to be muxed to its PWM mode, e.g. with `config-pin P9_14 pwm`.
+///
+/// The channel is exported when opened if needed, and disabled and
+/// unexported again when dropped.
+///
+/// # Fields
+/// * `chip` - The directory of the PWM chip.
+/// * `path` - The directory of the channel.
+/// * `channel` - The channel number within the chip.
+/// * `exported` - Whether the channel was exported by this instance.
+///
+/// # Examples
+/// Against a fake sysfs tree:
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{fs, time::Duration};
+/// use bbb_hal::pwm::{PwmChannel, PwmPolarity};
+///
+/// let root = std::env::temp_dir().join(format!("bbb-hal-pwm-{}", std::process::id()));
+/// let channel_dir = root.join("pwmchip0/pwm1");
+/// fs::create_dir_all(&channel_dir)?;
+/// for file in ["export", "unexport"] {
+///     fs::write(root.join("pwmchip0").join(file), "")?;
+/// }
+/// for file in ["period", "duty_cycle", "polarity", "enable"] {
+///     fs::write(channel_dir.join(file), "0")?;
+/// }
+///
+/// let mut pwm = PwmChannel::open_at(&root, 0, 1)?;
+/// pwm.set_period(Duration::from_micros(100))?;
+/// pwm.set_duty_cycle(Duration::from_micros(25))?;
+/// pwm.set_polarity(PwmPolarity::Inversed)?;
+/// pwm.enable(true)?;
+///
+/// assert_eq!(fs::read_to_string(channel_dir.join("period"))?, "100000");
+/// assert_eq!(pwm.duty_cycle()?, Duration::from_micros(25));
+/// assert_eq!(fs::read_to_string(channel_dir.join("polarity"))?, "inversed");
+/// assert!(pwm.set_duty_cycle(Duration::from_millis(1)).is_err());
+///
+/// drop(pwm);
+/// assert_eq!(fs::read_to_string(channel_dir.join("enable"))?, "0");
+/// # fs::remove_dir_all(&root)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PwmChannel {
+    chip: PathBuf,
+    path: PathBuf,
+    channel: u32,
+    exported: bool,
+}
+
+impl PwmChannel {
+    /// Open a channel of a PWM chip under [`SYSFS_ROOT`].
+    ///
+    /// # Arguments
+    /// * `chip` - The number of the `pwmchipN` directory.
+    /// * `channel` - The channel number within the chip.
+    ///
+    /// # Returns
+    /// * `Result<PwmChannel, Box<dyn Error>>` - The channel, exported if it was not yet.
+    pub fn open(chip: u32, channel: u32) -> Result<Self, Box<dyn Error>> {
+        Self::open_at(SYSFS_ROOT, chip, channel)
+    }
+
+    /// Open a channel of a PWM chip under another sysfs root, e.g. a fake tree in tests.
+    ///
+    /// # Arguments
+    /// * `root` - The directory containing the `pwmchipN` directories.
+    /// * `chip` - The number of the `pwmchipN` directory.
+    /// * `channel` - The channel number within the chip.
+    ///
+    /// # Returns
+    /// * `Result<PwmChannel, Box<dyn Error>>` - The channel, exported if it was not yet.
+    pub fn open_at<P: AsRef<Path>>(root: P, chip: u32, channel: u32) -> Result<Self, Box<dyn Error>> {
+
+        let chip = root.as_ref().join(format!("pwmchip{}", chip));
+        if !chip.is_dir() {
+            return Err(format!("PWM chip {} not found", chip.display()).into());
+        }
+
+        let path = chip.join(format!("pwm{}", channel));
+        let mut pwm = Self { chip, path, channel, exported: false };
+
+        if !pwm.path.is_dir() {
+            pwm.write_chip("export", channel)?;
+            pwm.exported = true;
+            pwm.wait_exported()?;
+        }
+
+        Ok(pwm)
+    }
+
+    /// Open a channel of the PWM chip of a given device, e.g. `48302200.pwm`.
+    ///
+    /// # Arguments
+    /// * `root` - The directory containing the `pwmchipN` directories, usually [`SYSFS_ROOT`].
+    /// * `device` - The name of the device the chip belongs to.
+    /// * `channel` - The channel number within the chip.
+    ///
+    /// # Returns
+    /// * `Result<PwmChannel, Box<dyn Error>>` - The channel, or an error listing the chips found.
+    pub fn open_device<P: AsRef<Path>>(root: P, device: &str, channel: u32) -> Result<Self, Box<dyn Error>> {
+
+        let root = root.as_ref();
+        let mut found = Vec::new();
+
+        for entry in fs::read_dir(root).map_err(|e| format!("Cannot read {}: {}", root.display(), e))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(number) = name.strip_prefix("pwmchip").and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+
+            let target = fs::read_link(entry.path().join("device")).unwrap_or_default();
+            let target = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if target == device {
+                return Self::open_at(root, number, channel);
+            }
+            found.push(format!("{} [{}]", name, target));
+        }
+
+        Err(format!("No PWM chip for device {}, found: {}", device, found.join(", ")).into())
+    }
+
+    /// The channel number within the chip.
+    pub fn channel(&self) -> u32 {
+        self.channel
+    }
+
+    /// Get the period of the signal.
+    pub fn period(&self) -> Result<Duration, Box<dyn Error>> {
+        Ok(Duration::from_nanos(self.read("period")?))
+    }
+
+    /// Set the period of the signal, shortening the duty cycle first if it would not fit.
+    ///
+    /// # Arguments
+    /// * `period` - The new period.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the attribute cannot be written.
+    pub fn set_period(&mut self, period: Duration) -> Result<(), Box<dyn Error>> {
+
+        if self.duty_cycle()? > period {
+            self.write("duty_cycle", 0)?;
+        }
+
+        self.write("period", period.as_nanos())
+    }
+
+    /// Get the time the output is active during every period.
+    pub fn duty_cycle(&self) -> Result<Duration, Box<dyn Error>> {
+        Ok(Duration::from_nanos(self.read("duty_cycle")?))
+    }
+
+    /// Set the time the output is active during every period.
+    ///
+    /// # Arguments
+    /// * `duty` - The active time, at most the period.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the duty cycle is longer than the period
+    ///   or the attribute cannot be written.
+    pub fn set_duty_cycle(&mut self, duty: Duration) -> Result<(), Box<dyn Error>> {
+
+        let period = self.period()?;
+        if duty > period {
+            return Err(format!("Duty cycle {:?} longer than the period {:?}", duty, period).into());
+        }
+
+        self.write("duty_cycle", duty.as_nanos())
+    }
+
+    /// Set the level of the output during the duty cycle.
+    ///
+    /// The kernel only accepts a polarity change while the channel is disabled.
+    ///
+    /// # Arguments
+    /// * `polarity` - The new polarity.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the attribute cannot be written.
+    pub fn set_polarity(&mut self, polarity: PwmPolarity) -> Result<(), Box<dyn Error>> {
+        self.write("polarity", polarity)
+    }
+
+    /// Start or stop the signal.
+    ///
+    /// # Arguments
+    /// * `on` - Whether the signal is output.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the attribute cannot be written.
+    pub fn enable(&mut self, on: bool) -> Result<(), Box<dyn Error>> {
+        self.write("enable", on as u8)
+    }
+
+    /// Set the duty cycle matching a brightness level.
+    ///
+    /// # Arguments
+    /// * `level` - The brightness level, from 0 (off) to [`MAX_BRIGHTNESS`](crate::brightness::MAX_BRIGHTNESS).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the level is out of range or an attribute
+    ///   cannot be accessed.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn Error>> {
+
+        brightness::validate(level)?;
+
+        let duty = brightness::scale(level, self.period()?);
+        self.set_duty_cycle(duty)
+    }
+
+    fn wait_exported(&self) -> Result<(), Box<dyn Error>> {
+
+        let start = Instant::now();
+        while !self.path.join("enable").exists() {
+            if start.elapsed() > EXPORT_TIMEOUT {
+                return Err(format!("PWM channel {} not created after export", self.path.display()).into());
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(())
+    }
+
+    fn read(&self, attr: &str) -> Result<u64, Box<dyn Error>> {
+
+        let path = self.path.join(attr);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+
+        text.trim()
+            .parse()
+            .map_err(|_| format!("Invalid value in {}: {:?}", path.display(), text).into())
+    }
+
+    fn write<T: fmt::Display>(&self, attr: &str, value: T) -> Result<(), Box<dyn Error>> {
+        let path = self.path.join(attr);
+        fs::write(&path, value.to_string())
+            .map_err(|e| format!("Cannot write {} to {}: {}", value, path.display(), e).into())
+    }
+
+    fn write_chip(&self, attr: &str, value: u32) -> Result<(), Box<dyn Error>> {
+        let path = self.chip.join(attr);
+        fs::write(&path, value.to_string())
+            .map_err(|e| format!("Cannot write {} to {}: {}", value, path.display(), e).into())
+    }
+}
+
+/// Drop implementation to stop the signal and release a channel exported by this instance.
+impl Drop for PwmChannel {
+    fn drop(&mut self) {
+        let _ = self.enable(false);
+        if self.exported {
+            let _ = self.write_chip("unexport", self.channel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fake `pwmchip0` with its attribute files, removed on drop.
+    struct FakeSysfs {
+        root: PathBuf,
+    }
+
+    impl FakeSysfs {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("bbb-hal-pwm-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(root.join("pwmchip0")).unwrap();
+            for file in ["export", "unexport"] {
+                fs::write(root.join("pwmchip0").join(file), "").unwrap();
+            }
+            Self { root }
+        }
+
+        fn create_channel(&self, channel: u32) {
+            create_channel(&self.root, channel);
+        }
+
+        fn read(&self, file: &str) -> String {
+            fs::read_to_string(self.root.join("pwmchip0").join(file)).unwrap()
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// Create the channel directory, as the kernel does on export.
+    fn create_channel(root: &Path, channel: u32) {
+        let dir = root.join(format!("pwmchip0/pwm{}", channel));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["period", "duty_cycle", "polarity", "enable"] {
+            fs::write(dir.join(file), "0").unwrap();
+        }
+    }
+
+    #[test]
+    fn missing_channel_is_exported_and_unexported() {
+        let sysfs = FakeSysfs::new("export");
+
+        // the kernel creating the channel once it is exported
+        let root = sysfs.root.clone();
+        let kernel = thread::spawn(move || {
+            while fs::read_to_string(root.join("pwmchip0/export")).unwrap().is_empty() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            create_channel(&root, 1);
+        });
+        let pwm = PwmChannel::open_at(&sysfs.root, 0, 1).unwrap();
+        kernel.join().unwrap();
+
+        assert_eq!(sysfs.read("export"), "1");
+        fs::write(sysfs.root.join("pwmchip0/pwm1/enable"), "1").unwrap();
+        drop(pwm);
+        assert_eq!(sysfs.read("pwm1/enable"), "0");
+        assert_eq!(sysfs.read("unexport"), "1");
+    }
+
+    #[test]
+    fn exported_channel_is_left_exported() {
+        let sysfs = FakeSysfs::new("exported");
+        sysfs.create_channel(0);
+
+        drop(PwmChannel::open_at(&sysfs.root, 0, 0).unwrap());
+        assert_eq!(sysfs.read("export"), "");
+        assert_eq!(sysfs.read("unexport"), "");
+    }
+
+    #[test]
+    fn export_times_out_without_the_kernel() {
+        let sysfs = FakeSysfs::new("timeout");
+        assert!(PwmChannel::open_at(&sysfs.root, 0, 0).is_err());
+    }
+
+    #[test]
+    fn period_duty_cycle_and_enable_in_nanoseconds() {
+        let sysfs = FakeSysfs::new("attributes");
+        sysfs.create_channel(0);
+        let mut pwm = PwmChannel::open_at(&sysfs.root, 0, 0).unwrap();
+
+        pwm.set_period(Duration::from_micros(200)).unwrap();
+        pwm.set_duty_cycle(Duration::from_micros(150)).unwrap();
+        pwm.enable(true).unwrap();
+        assert_eq!((sysfs.read("pwm0/period"), sysfs.read("pwm0/duty_cycle"), sysfs.read("pwm0/enable")),
+            ("200000".to_string(), "150000".to_string(), "1".to_string()));
+
+        // a shorter period than the duty cycle clears the duty cycle first
+        pwm.set_period(Duration::from_micros(100)).unwrap();
+        assert_eq!((sysfs.read("pwm0/period"), sysfs.read("pwm0/duty_cycle")), ("100000".to_string(), "0".to_string()));
+        assert!(pwm.set_duty_cycle(Duration::from_micros(101)).is_err());
+    }
+
+    #[test]
+    fn brightness_scales_the_duty_cycle() {
+        let sysfs = FakeSysfs::new("brightness");
+        sysfs.create_channel(0);
+        let mut pwm = PwmChannel::open_at(&sysfs.root, 0, 0).unwrap();
+        pwm.set_period(Duration::from_micros(150)).unwrap();
+
+        for (level, duty) in [(0, "0"), (5, "50000"), (15, "150000")] {
+            pwm.set_brightness(level).unwrap();
+            assert_eq!(sysfs.read("pwm0/duty_cycle"), duty, "level {}", level);
+        }
+        assert!(pwm.set_brightness(16).is_err());
+        assert_eq!(sysfs.read("pwm0/duty_cycle"), "150000");
+    }
+}
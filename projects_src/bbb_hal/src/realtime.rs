@@ -0,0 +1,223 @@
+use std::{
+    error::Error,
+    fmt, io, mem,
+    time::Duration,
+};
+
+/// Real-time settings of the thread scanning a display.
+///
+/// Applying them needs `CAP_SYS_NICE` and `CAP_IPC_LOCK`, i.e. usually root.
+///
+/// # Fields
+/// * `priority` - The `SCHED_FIFO` priority, from 1 to 99.
+/// * `cpu` - The CPU the thread is pinned to, if any.
+/// * `lock_memory` - Whether all pages of the process are locked in RAM, so
+///   the scan never waits on a page fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RealtimeConfig {
+    pub priority: i32,
+    pub cpu: Option<usize>,
+    pub lock_memory: bool,
+}
+
+impl Default for RealtimeConfig {
+    fn default() -> Self {
+        Self {
+            priority: 80,
+            cpu: None,
+            lock_memory: true,
+        }
+    }
+}
+
+impl RealtimeConfig {
+    /// Apply the settings to the calling thread.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the priority is out of range or a setting is refused.
+    pub fn apply(&self) -> Result<(), Box<dyn Error>> {
+
+        if !(1..=99).contains(&self.priority) {
+            return Err(format!("Real-time priority {} out of range, expected 1 to 99", self.priority).into());
+        }
+
+        if self.lock_memory {
+            // SAFETY: mlockall only takes flags.
+            if unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) } != 0 {
+                return Err(format!("Cannot lock memory: {}", io::Error::last_os_error()).into());
+            }
+        }
+
+        if let Some(cpu) = self.cpu {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(format!("CPU {} out of range", cpu).into());
+            }
+
+            // SAFETY: the set is zero-initialised and the CPU index checked against its size.
+            let result = unsafe {
+                let mut set: libc::cpu_set_t = mem::zeroed();
+                libc::CPU_SET(cpu, &mut set);
+                libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set)
+            };
+            if result != 0 {
+                return Err(format!("Cannot pin to CPU {}: {}", cpu, io::Error::last_os_error()).into());
+            }
+        }
+
+        let param = libc::sched_param { sched_priority: self.priority };
+        // SAFETY: the parameter outlives the call, which only reads it.
+        let result = unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+        if result != 0 {
+            return Err(format!("Cannot set SCHED_FIFO priority {}: {}", self.priority, io::Error::from_raw_os_error(result)).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Statistics of the wake-up latency of a [`DeadlineTimer`].
+///
+/// # Fields
+/// * `waits` - The count of deadlines slept until.
+/// * `overruns` - The count of deadlines already passed when the wait started.
+/// * `min` - The shortest wake-up latency.
+/// * `max` - The longest wake-up latency.
+/// * `total` - The sum of all wake-up latencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JitterStats {
+    pub waits: u64,
+    pub overruns: u64,
+    pub min: Duration,
+    pub max: Duration,
+    pub total: Duration,
+}
+
+impl JitterStats {
+    /// Get the mean wake-up latency.
+    pub fn mean(&self) -> Duration {
+        match self.waits {
+            0 => Duration::ZERO,
+            waits => self.total / waits as u32,
+        }
+    }
+
+    fn record(&mut self, latency: Duration) {
+        if self.waits == 0 || latency < self.min {
+            self.min = latency;
+        }
+        self.max = self.max.max(latency);
+        self.total += latency;
+        self.waits += 1;
+    }
+}
+
+impl fmt::Display for JitterStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} waits, latency min {:?} mean {:?} max {:?}, {} overruns",
+            self.waits, self.min, self.mean(), self.max, self.overruns)
+    }
+}
+
+/// Timer sleeping until absolute deadlines on `CLOCK_MONOTONIC`.
+///
+/// Every wait is counted from the previous deadline rather than from the
+/// wake-up, so late wake-ups do not accumulate into a slower scan. A
+/// deadline already passed when the wait starts counts as an overrun and
+/// restarts the schedule from the current time.
+///
+/// # Fields
+/// * `next` - The previous deadline, `None` before the first wait.
+/// * `stats` - The wake-up latencies measured so far.
+///
+/// # Examples
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use bbb_hal::realtime::DeadlineTimer;
+///
+/// let mut timer = DeadlineTimer::new();
+/// for _ in 0..10 {
+///     timer.wait(Duration::from_micros(500))?;
+/// }
+/// assert_eq!(timer.stats().waits + timer.stats().overruns, 10);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeadlineTimer {
+    next: Option<Duration>,
+    stats: JitterStats,
+}
+
+impl DeadlineTimer {
+    /// Create a timer whose schedule starts at the first wait.
+    ///
+    /// # Returns
+    /// * `DeadlineTimer` - A new instance of the DeadlineTimer struct.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sleep until an interval after the previous deadline.
+    ///
+    /// # Arguments
+    /// * `interval` - The time from the previous deadline, or from now on the first wait.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the clock cannot be read or slept on.
+    pub fn wait(&mut self, interval: Duration) -> Result<(), Box<dyn Error>> {
+
+        let now = monotonic_now()?;
+        let deadline = self.next.unwrap_or(now) + interval;
+
+        if deadline < now {
+            self.stats.overruns += 1;
+            self.next = Some(now);
+            return Ok(());
+        }
+
+        sleep_until(deadline)?;
+        self.stats.record(monotonic_now()?.saturating_sub(deadline));
+        self.next = Some(deadline);
+
+        Ok(())
+    }
+
+    /// Get the wake-up latencies measured so far.
+    pub fn stats(&self) -> JitterStats {
+        self.stats
+    }
+}
+
+/// Read `CLOCK_MONOTONIC`.
+fn monotonic_now() -> Result<Duration, Box<dyn Error>> {
+
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: the timespec is valid for writes for the duration of the call.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) } != 0 {
+        return Err(format!("Cannot read the monotonic clock: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Sleep until an absolute time on `CLOCK_MONOTONIC`, resuming after signals.
+fn sleep_until(deadline: Duration) -> Result<(), Box<dyn Error>> {
+
+    let ts = libc::timespec {
+        tv_sec: deadline.as_secs() as libc::time_t,
+        tv_nsec: deadline.subsec_nanos() as libc::c_long,
+    };
+
+    loop {
+        // SAFETY: the timespec outlives the call, the remaining time is unused with TIMER_ABSTIME.
+        let result = unsafe {
+            libc::clock_nanosleep(libc::CLOCK_MONOTONIC, libc::TIMER_ABSTIME, &ts, std::ptr::null_mut())
+        };
+        match result {
+            0 => return Ok(()),
+            libc::EINTR => continue,
+            errno => return Err(format!("Cannot sleep on the monotonic clock: {}", io::Error::from_raw_os_error(errno)).into()),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    brightness::Brightness,
+    multiplex::{FrameBuffer, MultiplexedDisplay, ScanTiming},
+    realtime::{DeadlineTimer, JitterStats, RealtimeConfig},
+};
+
+/// Refresh rate used when the caller has no preference, in full scans per second.
+pub const DEFAULT_RATE_HZ: u32 = 100;
+
+/// Background thread scanning the frame buffer of a multiplexed display.
+///
+/// The thread owns the display and keeps scanning its [`FrameBuffer`] until
+/// it is stopped, so callers only have to update the buffer, from any thread.
+///
+/// # Fields
+/// * `buffer` - The frame buffer scanned by the thread.
+/// * `brightness` - The brightness level of the scanned display.
+/// * `jitter` - The wake-up latencies of a real-time scan, updated after every scan.
+/// * `stop` - Set to ask the thread to finish its scan, which leaves the display blank, and exit.
+/// * `handle` - The thread, returning the display once stopped.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{thread, time::Duration};
+/// use bbb_hal::{multiplex::{MultiplexedDisplay, ScanTiming}, refresh::RefreshThread};
+///
+/// let refresh = RefreshThread::spawn(MultiplexedDisplay::new()?, ScanTiming::from_rate(100)?)?;
+/// let buffer = refresh.buffer();
+/// for n in 0..100 {
+///     buffer.show_number(n);
+///     thread::sleep(Duration::from_millis(100));
+/// }
+/// refresh.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RefreshThread {
+    buffer: FrameBuffer,
+    brightness: Brightness,
+    jitter: Arc<Mutex<Option<JitterStats>>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Result<MultiplexedDisplay, String>>>,
+}
+
+impl RefreshThread {
+    /// Start scanning a display in the background.
+    ///
+    /// # Arguments
+    /// * `display` - The display to scan, its current frame is shown right away.
+    /// * `timing` - The blanking and dwell time of every digit, see [`ScanTiming::from_rate`].
+    ///
+    /// # Returns
+    /// * `Result<RefreshThread, Box<dyn Error>>` - The running thread, or an error if the thread cannot be spawned.
+    pub fn spawn(display: MultiplexedDisplay, timing: ScanTiming) -> Result<Self, Box<dyn Error>> {
+        Self::spawn_with(display, timing, None)
+    }
+
+    /// Start scanning a display in the background with real-time scheduling.
+    ///
+    /// The thread applies the real-time settings to itself and sleeps until
+    /// absolute deadlines, see [`DeadlineTimer`], recording its wake-up
+    /// latencies in [`RefreshThread::jitter`].
+    ///
+    /// # Arguments
+    /// * `display` - The display to scan, its current frame is shown right away.
+    /// * `timing` - The blanking and dwell time of every digit, see [`ScanTiming::from_rate`].
+    /// * `config` - The scheduling priority, CPU and memory locking of the thread.
+    ///
+    /// # Returns
+    /// * `Result<RefreshThread, Box<dyn Error>>` - The running thread, or an error if the thread cannot be
+    ///   spawned. A refused real-time setting stops the thread, see [`RefreshThread::stop`].
+    pub fn spawn_realtime(display: MultiplexedDisplay, timing: ScanTiming, config: RealtimeConfig) -> Result<Self, Box<dyn Error>> {
+        Self::spawn_with(display, timing, Some(config))
+    }
+
+    fn spawn_with(mut display: MultiplexedDisplay, timing: ScanTiming, config: Option<RealtimeConfig>) -> Result<Self, Box<dyn Error>> {
+
+        let buffer = display.buffer();
+        let brightness = display.brightness();
+        let jitter = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stats = Arc::clone(&jitter);
+        let stopped = Arc::clone(&stop);
+        let handle = thread::Builder::new()
+            .name("display-refresh".to_string())
+            .spawn(move || {
+                let Some(config) = config else {
+                    while !stopped.load(Ordering::Relaxed) {
+                        display.refresh(timing).map_err(|e| e.to_string())?;
+                    }
+                    return Ok(display);
+                };
+
+                config.apply().map_err(|e| e.to_string())?;
+
+                let mut timer = DeadlineTimer::new();
+                while !stopped.load(Ordering::Relaxed) {
+                    display.refresh_with(timing, |time| timer.wait(time)).map_err(|e| e.to_string())?;
+                    *stats.lock().unwrap_or_else(|e| e.into_inner()) = Some(timer.stats());
+                }
+                Ok(display)
+            })
+            .map_err(|e| format!("Cannot spawn display refresh thread: {}", e))?;
+
+        Ok(Self { buffer, brightness, jitter, stop, handle: Some(handle) })
+    }
+
+    /// Get a handle on the frame buffer scanned by the thread.
+    pub fn buffer(&self) -> FrameBuffer {
+        self.buffer.clone()
+    }
+
+    /// Get a handle on the brightness level of the scanned display.
+    pub fn brightness(&self) -> Brightness {
+        self.brightness.clone()
+    }
+
+    /// Get the wake-up latencies of the scan so far.
+    ///
+    /// # Returns
+    /// * `Option<JitterStats>` - The statistics, or `None` if the thread was not spawned
+    ///   with [`RefreshThread::spawn_realtime`] or has not completed a scan yet.
+    pub fn jitter(&self) -> Option<JitterStats> {
+        *self.jitter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether the thread is still scanning, `false` once it stopped on an error.
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Get the error that stopped the thread, if it stopped.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - The error that stopped the thread, or `Ok` while it is scanning.
+    pub fn check(&mut self) -> Result<(), Box<dyn Error>> {
+        match &self.handle {
+            Some(handle) if !handle.is_finished() => Ok(()),
+            _ => self.join().map(|_| ()),
+        }
+    }
+
+    /// Stop scanning and blank the display.
+    ///
+    /// # Returns
+    /// * `Result<MultiplexedDisplay, Box<dyn Error>>` - The display, or the error that stopped the thread.
+    pub fn stop(mut self) -> Result<MultiplexedDisplay, Box<dyn Error>> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<MultiplexedDisplay, Box<dyn Error>> {
+
+        self.stop.store(true, Ordering::Relaxed);
+
+        let handle = self.handle.take().ok_or("Display refresh thread already stopped")?;
+        match handle.join() {
+            Ok(result) => result.map_err(|e| format!("Display refresh failed: {}", e).into()),
+            Err(_) => Err("Display refresh thread panicked".into()),
+        }
+    }
+}
+
+impl Drop for RefreshThread {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            let _ = self.join();
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    error::Error,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::fd::AsRawFd,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::calendar::{self, civil_from_days, days_from_civil};
+
+/// I2C address of the DS3231.
+pub const DS3231_ADDRESS: u16 = 0x68;
+
+/// `ioctl` selecting the address of the following reads and writes, from `linux/i2c-dev.h`.
+const I2C_SLAVE: u64 = 0x0703;
+
+/// First time register: seconds, minutes, hours, weekday, date, month/century, year.
+const REG_TIME: u8 = 0x00;
+
+/// Status register, whose bit 7 (`OSF`) is set when the oscillator stopped.
+const REG_STATUS: u8 = 0x0F;
+const OSF: u8 = 0x80;
+
+/// Years the DS3231 can store, with its century bit.
+const YEARS: std::ops::RangeInclusive<i64> = 2000..=2199;
+
+/// A DS3231 battery-backed real-time clock on an I2C bus, keeping UTC.
+///
+/// # Fields
+/// * `file` - The I2C bus device, bound to the RTC address.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use bbb_hal::rtc::Ds3231;
+///
+/// // P9_19/P9_20, enabled by default
+/// let mut rtc = Ds3231::open(2)?;
+/// println!("{:?}", rtc.read_time()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ds3231 {
+    file: File,
+}
+
+impl Ds3231 {
+    /// Open the RTC on `/dev/i2c-<bus>`.
+    ///
+    /// # Arguments
+    /// * `bus` - The I2C bus number.
+    ///
+    /// # Returns
+    /// * `Result<Ds3231, Box<dyn Error>>` - The RTC, or an error if the bus cannot be opened.
+    pub fn open(bus: u8) -> Result<Self, Box<dyn Error>> {
+        Self::open_at(format!("/dev/i2c-{}", bus))
+    }
+
+    /// Open the RTC on an I2C bus device.
+    ///
+    /// # Arguments
+    /// * `path` - The bus device, e.g. `/dev/i2c-2`.
+    ///
+    /// # Returns
+    /// * `Result<Ds3231, Box<dyn Error>>` - The RTC, or an error if the bus cannot be opened.
+    pub fn open_at<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("Cannot open I2C bus {}: {}", path.display(), e))?;
+
+        // SAFETY: I2C_SLAVE takes the address as an integer argument and the descriptor is open.
+        if unsafe { libc::ioctl(file.as_raw_fd(), I2C_SLAVE as _, DS3231_ADDRESS as libc::c_ulong) } < 0 {
+            return Err(format!("Cannot address the RTC on {}: {}", path.display(), io::Error::last_os_error()).into());
+        }
+
+        Ok(Self { file })
+    }
+
+    /// Read the time of the RTC.
+    ///
+    /// # Returns
+    /// * `Result<SystemTime, Box<dyn Error>>` - The time, or an error if the RTC cannot be read
+    ///   or lost its time because its oscillator stopped.
+    pub fn read_time(&mut self) -> Result<SystemTime, Box<dyn Error>> {
+
+        if self.read_registers(REG_STATUS, 1)?[0] & OSF != 0 {
+            return Err("RTC time is invalid, its oscillator stopped".into());
+        }
+
+        let regs = self.read_registers(REG_TIME, 7)?;
+        let second = bcd(regs[0] & 0x7F);
+        let minute = bcd(regs[1] & 0x7F);
+        let hour = if regs[2] & 0x40 != 0 {
+            // 12-hour mode, bit 5 is PM
+            bcd(regs[2] & 0x1F) % 12 + if regs[2] & 0x20 != 0 { 12 } else { 0 }
+        } else {
+            bcd(regs[2] & 0x3F)
+        };
+        let day = bcd(regs[4] & 0x3F);
+        let month = bcd(regs[5] & 0x1F);
+        let year = 2000 + bcd(regs[6]) as i64 + if regs[5] & 0x80 != 0 { 100 } else { 0 };
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+            return Err(format!("RTC holds an invalid time: {:?}", regs).into());
+        }
+
+        let secs = days_from_civil(year, month as u32, day as u32) * 86_400
+            + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+
+        Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64))
+    }
+
+    /// Set the RTC, in 24-hour mode, and clear its oscillator stop flag.
+    ///
+    /// # Arguments
+    /// * `time` - The time to store, rounded down to the second.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the time is out of the RTC range or cannot be written.
+    pub fn write_time(&mut self, time: SystemTime) -> Result<(), Box<dyn Error>> {
+
+        let secs = time.duration_since(SystemTime::UNIX_EPOCH).map_err(|_| "Cannot store a time before 1970 in the RTC")?.as_secs() as i64;
+        let days = secs.div_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !YEARS.contains(&year) {
+            return Err(format!("Cannot store year {} in the RTC", year).into());
+        }
+
+        let of_day = secs.rem_euclid(86_400);
+        let century = if year >= 2100 { 0x80 } else { 0 };
+        // weekday from 1 (Sunday) to 7
+        let weekday = calendar::weekday(days) as u8 + 1;
+
+        self.file.write_all(&[
+            REG_TIME,
+            to_bcd((of_day % 60) as u8),
+            to_bcd((of_day / 60 % 60) as u8),
+            to_bcd((of_day / 3600) as u8),
+            weekday,
+            to_bcd(day as u8),
+            to_bcd(month as u8) | century,
+            to_bcd((year % 100) as u8),
+        ]).map_err(|e| format!("Cannot write the RTC time: {}", e))?;
+
+        let status = self.read_registers(REG_STATUS, 1)?[0];
+        self.file
+            .write_all(&[REG_STATUS, status & !OSF])
+            .map_err(|e| format!("Cannot clear the RTC oscillator stop flag: {}", e).into())
+    }
+
+    fn read_registers(&mut self, first: u8, count: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut regs = vec![0; count];
+        self.file
+            .write_all(&[first])
+            .and_then(|_| self.file.read_exact(&mut regs))
+            .map_err(|e| format!("Cannot read RTC register {:#04x}: {}", first, e))?;
+        Ok(regs)
+    }
+}
+
+fn bcd(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0F)
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcd_values() {
+        for (value, encoded) in [(0, 0x00), (9, 0x09), (10, 0x10), (23, 0x23), (59, 0x59), (99, 0x99)] {
+            assert_eq!(to_bcd(value), encoded, "{}", value);
+            assert_eq!(bcd(encoded), value, "{:#04x}", encoded);
+        }
+    }
+
+    #[test]
+    fn bcd_round_trip() {
+        for value in 0..=99 {
+            assert_eq!(bcd(to_bcd(value)), value);
+        }
+        // every byte with two decimal nibbles
+        for encoded in (0..=0x99u8).filter(|b| b & 0x0F <= 9) {
+            assert_eq!(to_bcd(bcd(encoded)), encoded);
+        }
+    }
+}
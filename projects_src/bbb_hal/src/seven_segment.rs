@@ -0,0 +1,535 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use gpiocdev::line::Value;
+
+use crate::{
+    brightness::{Brightness, MAX_BRIGHTNESS},
+    font::Glyph,
+    gpio::{CdevBackend, GpioBackend},
+    header,
+    pwm::PwmChannel,
+    wiring::Wiring,
+};
+
+/// A segment of the display, named after the usual `A`..`G` + `DP` layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Segment {
+    A,
+    B,
+    C,
+    DP,
+    D,
+    E,
+    F,
+    G,
+}
+
+impl Segment {
+    /// All segments of the display, in wiring order.
+    pub const ALL: [Segment; 8] = [
+        Segment::A, Segment::B, Segment::C, Segment::DP,
+        Segment::D, Segment::E, Segment::F, Segment::G,
+    ];
+}
+
+/// Period of the software PWM dimming a single-digit display.
+const PWM_PERIOD: Duration = Duration::from_millis(4);
+
+/// Period given to a hardware PWM channel that has none yet.
+const HARDWARE_PWM_PERIOD: Duration = Duration::from_micros(100);
+
+/// Software PWM thread blinking the segments of a dimmed display.
+struct PwmThread {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Result<(), String>>,
+}
+
+/// A struct representing a seven-segment display.
+///
+/// Below [`MAX_BRIGHTNESS`], a software PWM thread turns the segments on and
+/// off every few milliseconds and is the only one writing them, unless a
+/// hardware PWM channel gating the common line is attached with
+/// [`SevenSegmentDisplay::set_hardware_pwm`].
+/// A multiplexed display dims within its scan instead.
+///
+/// # Fields
+/// * `backend` - The GPIO backend driving the segment lines, shared with the PWM thread.
+/// * `wiring` - The header pins and polarity of the segments.
+/// * `glyph` - The glyph currently shown, restored by the PWM thread after every off-time.
+/// * `brightness` - The brightness level of the display.
+/// * `pwm` - The software PWM thread, running while the display is dimmed.
+/// * `hardware_pwm` - The hardware PWM channel dimming the display, if any.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{thread, time::Duration};
+/// use bbb_hal::seven_segment::SevenSegmentDisplay;
+///
+/// let mut display = SevenSegmentDisplay::new()?;
+/// loop {
+///     for i in 0..=15 {
+///         display.set_digit(i)?;
+///         thread::sleep(Duration::from_millis(1000));
+///     }
+/// }
+/// # }
+/// ```
+pub struct SevenSegmentDisplay {
+    backend: Arc<Mutex<Box<dyn GpioBackend>>>,
+    wiring: Wiring,
+    glyph: Arc<Mutex<Glyph>>,
+    brightness: Brightness,
+    pwm: Option<PwmThread>,
+    hardware_pwm: Option<PwmChannel>,
+}
+
+impl SevenSegmentDisplay {
+    /// Create a new instance of the SevenSegmentDisplay struct.
+    ///
+    /// # Returns
+    /// * `SevenSegmentDisplay` - A new instance using the default wiring.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Self::with_wiring(Wiring::default())
+    }
+
+    /// Create a display with the given wiring.
+    ///
+    /// # Arguments
+    /// * `wiring` - The header pins and polarity of the segments.
+    ///
+    /// # Returns
+    /// * `SevenSegmentDisplay` - A new instance with all segments inactive.
+    pub fn with_wiring(wiring: Wiring) -> Result<Self, Box<dyn Error>> {
+        Self::with_backend(Box::new(CdevBackend::new()), wiring)
+    }
+
+    /// Create a display driving its segments through the given GPIO backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The GPIO backend, e.g. a `MockBackend` in tests.
+    /// * `wiring` - The header pins and polarity of the segments.
+    ///
+    /// # Returns
+    /// * `SevenSegmentDisplay` - A new instance with all segments inactive.
+    pub fn with_backend(mut backend: Box<dyn GpioBackend>, wiring: Wiring) -> Result<Self, Box<dyn Error>> {
+
+        let pins = wiring.segment_pins();
+        header::validate(&pins)?;
+        let gpios = pins.iter().map(|pin| pin.gpio()).collect::<Vec<_>>();
+
+        // segments inactive
+        backend.request_outputs(&gpios, Value::Inactive, wiring.segment_polarity.is_active_low())?;
+
+        Ok(Self {
+            backend: Arc::new(Mutex::new(backend)),
+            wiring,
+            glyph: Arc::new(Mutex::new(Glyph::BLANK)),
+            brightness: Brightness::default(),
+            pwm: None,
+            hardware_pwm: None,
+        })
+    }
+
+    /// The wiring of the display.
+    pub fn wiring(&self) -> &Wiring {
+        &self.wiring
+    }
+
+    /// The GPIO backend of the display, shared with the digit lines of a
+    /// multiplexed display.
+    pub(crate) fn backend_mut(&mut self) -> MutexGuard<'_, Box<dyn GpioBackend>> {
+        lock(&self.backend)
+    }
+
+    /// Get a handle on the brightness level of the display.
+    pub fn brightness(&self) -> Brightness {
+        self.brightness.clone()
+    }
+
+    /// Dim the display with a hardware PWM channel instead of the software PWM thread.
+    ///
+    /// The channel drives the common line of the display, or the gate of the
+    /// transistor switching it, so the segments stay lit continuously and the
+    /// duty cycle sets the brightness. A channel without period gets a 10 kHz one.
+    ///
+    /// # Arguments
+    /// * `channel` - The PWM channel, e.g. opened with [`PwmChannel::open_device`].
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the channel cannot be configured.
+    pub fn set_hardware_pwm(&mut self, mut channel: PwmChannel) -> Result<(), Box<dyn Error>> {
+
+        if channel.period()?.is_zero() {
+            channel.set_period(HARDWARE_PWM_PERIOD)?;
+        }
+        channel.set_brightness(self.brightness.get())?;
+        channel.enable(true)?;
+
+        self.stop_pwm()?;
+        self.hardware_pwm = Some(channel);
+
+        let glyph = *lock(&self.glyph);
+        self.set_glyph(glyph)
+    }
+
+    /// Dim the display, through the hardware PWM channel if one is attached or
+    /// by starting the software PWM thread if needed.
+    ///
+    /// Level 0 blanks the segments once and stops the thread, glyphs set
+    /// meanwhile are shown from the next level up.
+    ///
+    /// # Arguments
+    /// * `level` - The brightness level, from 0 (off) to [`MAX_BRIGHTNESS`].
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the level is out of range, the PWM channel
+    ///   cannot be written, the thread cannot be spawned or it stopped on a GPIO error.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn Error>> {
+        self.brightness.set(level)?;
+
+        if let Some(channel) = self.hardware_pwm.as_mut() {
+            channel.set_brightness(level)?;
+        } else if level == 0 || level == MAX_BRIGHTNESS {
+            // off or lit continuously, the thread may have stopped in its off-time
+            self.stop_pwm()?;
+        } else {
+            if !self.pwm_shows_glyph()? {
+                self.pwm = Some(self.spawn_pwm()?);
+            }
+            return Ok(());
+        }
+
+        // blanked once at level 0, the glyph is written again from the next level up
+        let glyph = if level > 0 { *lock(&self.glyph) } else { Glyph::BLANK };
+        let gpios = self.segment_gpios();
+        write_glyph(self.backend_mut().as_mut(), &gpios, glyph)
+    }
+
+    /// Whether the software PWM thread is running and shows the glyph.
+    ///
+    /// # Returns
+    /// * `Result<bool, Box<dyn Error>>` - An error if the thread stopped on a GPIO error.
+    fn pwm_shows_glyph(&mut self) -> Result<bool, Box<dyn Error>> {
+        match &self.pwm {
+            Some(pwm) if !pwm.handle.is_finished() => Ok(true),
+            Some(_) => self.stop_pwm().map(|_| false),
+            None => Ok(false),
+        }
+    }
+
+    fn spawn_pwm(&self) -> Result<PwmThread, Box<dyn Error>> {
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let backend = Arc::clone(&self.backend);
+        let glyph = Arc::clone(&self.glyph);
+        let brightness = self.brightness.clone();
+        let gpios = self.segment_gpios();
+
+        let handle = thread::Builder::new()
+            .name("segment-pwm".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    let on = brightness.scale(PWM_PERIOD);
+                    if !on.is_zero() {
+                        let glyph = *lock(&glyph);
+                        write_glyph(lock(&backend).as_mut(), &gpios, glyph).map_err(|e| e.to_string())?;
+                        thread::sleep(on);
+                    }
+                    if on < PWM_PERIOD {
+                        write_glyph(lock(&backend).as_mut(), &gpios, Glyph::BLANK).map_err(|e| e.to_string())?;
+                        thread::sleep(PWM_PERIOD - on);
+                    }
+                }
+                Ok(())
+            })
+            .map_err(|e| format!("Cannot spawn segment PWM thread: {}", e))?;
+
+        Ok(PwmThread { stop, handle })
+    }
+
+    fn stop_pwm(&mut self) -> Result<(), Box<dyn Error>> {
+
+        let Some(pwm) = self.pwm.take() else {
+            return Ok(());
+        };
+
+        pwm.stop.store(true, Ordering::Relaxed);
+        match pwm.handle.join() {
+            Ok(result) => result.map_err(|e| format!("Segment PWM failed: {}", e).into()),
+            Err(_) => Err("Segment PWM thread panicked".into()),
+        }
+    }
+
+    /// Get the GPIO numbers of the segments, in [`Segment::ALL`] order.
+    fn segment_gpios(&self) -> [u32; 8] {
+        Segment::ALL.map(|seg| self.wiring.segment(seg).gpio())
+    }
+
+    /// Set the value of a segment on the display.
+    ///
+    /// # Arguments
+    /// * `seg` - The segment to set.
+    /// * `value` - The value to set the segment to ( `Value::Active` lights it, whatever the polarity).
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    pub fn set_segment(&mut self, seg: Segment, value: Value) -> Result<(), Box<dyn Error>> {
+
+        let glyph = {
+            let mut glyph = lock(&self.glyph);
+            *glyph = glyph.with_segment(seg, value == Value::Active);
+            *glyph
+        };
+        if self.pwm_shows_glyph()? || self.brightness.get() == 0 {
+            return Ok(());
+        }
+
+        let pin = self.wiring.segment(seg);
+        let value = if glyph.contains(seg) { Value::Active } else { Value::Inactive };
+        self.backend_mut().set_line(pin.gpio(), value)
+    }
+
+    /// Show a glyph on the display, writing all segment lines at once, or
+    /// from the next on-time of the software PWM thread while it runs.
+    ///
+    /// # Arguments
+    /// * `glyph` - The segments to light, decimal point included.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if a pin is not initialized.
+    pub fn set_glyph(&mut self, glyph: Glyph) -> Result<(), Box<dyn Error>> {
+
+        *lock(&self.glyph) = glyph;
+        if self.pwm_shows_glyph()? || self.brightness.get() == 0 {
+            return Ok(());
+        }
+
+        let gpios = self.segment_gpios();
+        write_glyph(self.backend_mut().as_mut(), &gpios, glyph)
+    }
+
+    /// Clear all segments on the display.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the pin is not initialized.
+    pub fn clear_all(&mut self) -> Result<(), Box<dyn Error>> {
+        self.set_glyph(Glyph::BLANK)
+    }
+
+    /// Set a digit on the display.
+    ///
+    /// # Arguments
+    /// * `digit` - The digit to set. Must be between 0 and 15 (inclusive), shown in hexadecimal.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the digit is out of range or the pin is not initialized.
+    pub fn set_digit(&mut self, digit: u8) -> Result<(), Box<dyn Error>> {
+
+        let glyph = Glyph::from_digit(digit)
+            .ok_or_else(|| format!("Digit {} cannot be shown, expected 0 to 15", digit))?;
+
+        self.set_glyph(glyph)
+    }
+
+    /// Set a character on the display.
+    ///
+    /// # Arguments
+    /// * `c` - The character to set, see [`Glyph::from_char`] for the supported ones.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the character has no glyph or the pin is not initialized.
+    pub fn set_char(&mut self, c: char) -> Result<(), Box<dyn Error>> {
+
+        let glyph = Glyph::from_char(c)
+            .ok_or_else(|| format!("Character {:?} cannot be shown on a seven-segment display", c))?;
+
+        self.set_glyph(glyph)
+    }
+
+    /// Sets the value of decimal point segment.
+    ///
+    /// # Arguments
+    /// * `state` - The value to set the decimal point segment.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - Result of the operation.
+    pub fn set_decimal_point(&mut self, state: bool) -> Result<(), Box<dyn Error>> {
+        self.set_segment(Segment::DP, if state { Value::Active } else { Value::Inactive })
+    }
+}
+
+/// Drop implementation to stop the PWM thread and clear all segments when the object is dropped.
+impl Drop for SevenSegmentDisplay {
+    fn drop(&mut self) {
+        let _ = self.stop_pwm();
+        let _ = self.clear_all();
+    }
+}
+
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write all segment lines at once.
+///
+/// # Arguments
+/// * `backend` - The GPIO backend driving the segment lines.
+/// * `gpios` - The GPIO numbers of the segments, in [`Segment::ALL`] order.
+/// * `glyph` - The segments to light.
+fn write_glyph(backend: &mut dyn GpioBackend, gpios: &[u32; 8], glyph: Glyph) -> Result<(), Box<dyn Error>> {
+
+    let values = Segment::ALL.map(|seg| if glyph.contains(seg) { Value::Active } else { Value::Inactive });
+    let lines = gpios.iter().copied().zip(values).collect::<Vec<_>>();
+
+    backend.set_lines(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpio::mock::MockBackend;
+    use crate::wiring::DisplayType;
+
+    fn display() -> (SevenSegmentDisplay, MockBackend) {
+        let mock = MockBackend::new();
+        let display = SevenSegmentDisplay::with_backend(Box::new(mock.clone()), Wiring::default()).unwrap();
+        (display, mock)
+    }
+
+    /// The line writes showing a glyph on the default wiring.
+    fn glyph_writes(glyph: Glyph) -> Vec<(u32, Value)> {
+        let wiring = Wiring::default();
+        Segment::ALL
+            .map(|seg| (wiring.segment(seg).gpio(), if glyph.contains(seg) { Value::Active } else { Value::Inactive }))
+            .to_vec()
+    }
+
+    #[test]
+    fn set_digit_writes_all_segments_at_once() {
+        let (mut display, mock) = display();
+
+        display.set_digit(3).unwrap();
+        display.set_digit(0xA).unwrap();
+
+        let expected = [glyph_writes(Glyph::from_digit(3).unwrap()), glyph_writes(Glyph::from_digit(0xA).unwrap())].concat();
+        assert_eq!(mock.writes(), expected);
+    }
+
+    #[test]
+    fn set_digit_rejects_out_of_range() {
+        let (mut display, mock) = display();
+
+        assert!(display.set_digit(16).is_err());
+        assert!(mock.writes().is_empty());
+    }
+
+    #[test]
+    fn set_decimal_point_writes_one_line() {
+        let (mut display, mock) = display();
+
+        display.set_decimal_point(true).unwrap();
+
+        assert_eq!(mock.writes(), vec![(Wiring::default().segment(Segment::DP).gpio(), Value::Active)]);
+    }
+
+    #[test]
+    fn common_anode_segments_are_driven_low() {
+        let mock = MockBackend::new();
+        let mut wiring = Wiring::default();
+        wiring.set_display_type(DisplayType::CommonAnode);
+        let mut display = SevenSegmentDisplay::with_backend(Box::new(mock.clone()), wiring.clone()).unwrap();
+
+        // inactive segments idle high
+        for seg in Segment::ALL {
+            let gpio = wiring.segment(seg).gpio();
+            assert_eq!(mock.is_active_low(gpio), Some(true), "{:?}", seg);
+            assert_eq!(mock.is_high(gpio), Some(true), "{:?}", seg);
+        }
+
+        display.set_digit(1).unwrap();
+
+        let one = Glyph::from_digit(1).unwrap();
+        assert_eq!(mock.writes(), glyph_writes(one));
+        for seg in Segment::ALL {
+            assert_eq!(mock.is_high(wiring.segment(seg).gpio()), Some(!one.contains(seg)), "{:?}", seg);
+        }
+    }
+
+    #[test]
+    fn zero_brightness_blanks_once_without_thread() {
+        let (mut display, mock) = display();
+
+        display.set_digit(8).unwrap();
+        mock.clear_writes();
+        display.set_brightness(0).unwrap();
+        display.set_digit(8).unwrap();
+        thread::sleep(PWM_PERIOD * 3);
+
+        // blanked once, no thread left rewriting the segments
+        assert!(display.pwm.is_none());
+        assert_eq!(mock.writes(), glyph_writes(Glyph::BLANK));
+
+        mock.clear_writes();
+        display.set_brightness(MAX_BRIGHTNESS).unwrap();
+        assert_eq!(mock.writes(), glyph_writes(Glyph::from_digit(8).unwrap()));
+    }
+
+    #[test]
+    fn dimmed_glyph_changes_go_through_the_pwm_thread() {
+        let (mut display, mock) = display();
+
+        display.set_brightness(MAX_BRIGHTNESS / 2).unwrap();
+        display.set_digit(8).unwrap();
+        thread::sleep(PWM_PERIOD * 3);
+        display.set_digit(1).unwrap();
+        // a write of 8 started before the change may still land
+        thread::sleep(PWM_PERIOD);
+        mock.clear_writes();
+        thread::sleep(PWM_PERIOD * 3);
+
+        // the digit 1 alternating with blank, 8 no longer shown
+        let writes = mock.writes();
+        let (one, blank) = (glyph_writes(Glyph::from_digit(1).unwrap()), glyph_writes(Glyph::BLANK));
+        assert!(writes.chunks(8).any(|chunk| chunk == one));
+        assert!(writes.chunks(8).all(|chunk| chunk == one || chunk == blank));
+    }
+
+    #[test]
+    fn full_brightness_stops_the_pwm_thread() {
+        let (mut display, mock) = display();
+
+        display.set_digit(8).unwrap();
+        display.set_brightness(1).unwrap();
+        thread::sleep(PWM_PERIOD * 3);
+        display.set_brightness(MAX_BRIGHTNESS).unwrap();
+        assert!(display.pwm.is_none());
+
+        // lit continuously, nothing written any more
+        let writes = mock.writes();
+        assert_eq!(writes[writes.len() - 8..], glyph_writes(Glyph::from_digit(8).unwrap())[..]);
+        mock.clear_writes();
+        thread::sleep(PWM_PERIOD * 3);
+        assert!(mock.writes().is_empty());
+    }
+
+    #[test]
+    fn drop_clears_the_display() {
+        let (mut display, mock) = display();
+
+        display.set_digit(8).unwrap();
+        drop(display);
+
+        let writes = mock.writes();
+        assert_eq!(writes[writes.len() - 8..], glyph_writes(Glyph::BLANK)[..]);
+    }
+}
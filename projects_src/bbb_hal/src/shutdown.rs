@@ -0,0 +1,110 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    flag,
+};
+
+/// Longest time [`Shutdown::sleep`] goes without checking the flag.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Shutdown request shared by the run loops of a program.
+///
+/// Once installed, SIGINT, SIGTERM and SIGHUP set the flag instead of
+/// killing the process, so the loops can return and the displays blank
+/// their segments and release their GPIO lines when dropped. A second
+/// signal while the flag is set terminates the process right away.
+///
+/// # Fields
+/// * `flag` - Set when a shutdown was requested.
+///
+/// # Examples
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use bbb_hal::{seven_segment::SevenSegmentDisplay, shutdown::Shutdown};
+///
+/// let shutdown = Shutdown::install()?;
+/// let mut display = SevenSegmentDisplay::new()?;
+/// let mut digit = 0;
+/// while shutdown.sleep(Duration::from_secs(1)) {
+///     digit = (digit + 1) % 10;
+///     display.set_digit(digit)?;
+/// }
+/// // dropping the display blanks it
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    flag: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    /// Create a flag only set by [`Shutdown::request`], e.g. in tests.
+    ///
+    /// # Returns
+    /// * `Shutdown` - A new instance of the Shutdown struct.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a flag set by SIGINT, SIGTERM and SIGHUP.
+    ///
+    /// # Returns
+    /// * `Result<Shutdown, Box<dyn Error>>` - The flag, or an error if a signal handler cannot be registered.
+    pub fn install() -> Result<Self, Box<dyn Error>> {
+
+        let shutdown = Self::new();
+
+        for signal in [SIGINT, SIGTERM, SIGHUP] {
+            // registered first, so it only fires when the flag was already set by a previous signal
+            flag::register_conditional_shutdown(signal, 1, Arc::clone(&shutdown.flag))
+                .map_err(|e| format!("Cannot register handler for signal {}: {}", signal, e))?;
+            flag::register(signal, Arc::clone(&shutdown.flag))
+                .map_err(|e| format!("Cannot register handler for signal {}: {}", signal, e))?;
+        }
+
+        Ok(shutdown)
+    }
+
+    /// Ask the run loops to stop.
+    pub fn request(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether a shutdown was requested.
+    pub fn is_requested(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// Sleep, waking up early when a shutdown is requested.
+    ///
+    /// # Arguments
+    /// * `duration` - The time to sleep.
+    ///
+    /// # Returns
+    /// * `bool` - `true` if the loop may go on, `false` if a shutdown was requested.
+    pub fn sleep(&self, duration: Duration) -> bool {
+
+        let end = Instant::now() + duration;
+
+        while !self.is_requested() {
+            let left = end.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return true;
+            }
+            thread::sleep(left.min(POLL_INTERVAL));
+        }
+
+        false
+    }
+}
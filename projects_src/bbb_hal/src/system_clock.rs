@@ -0,0 +1,193 @@
+use std::{
+    error::Error,
+    io, mem,
+    time::{Duration, SystemTime},
+};
+
+/// Earliest plausible time, 2024-01-01 00:00:00 UTC: anything before means
+/// nothing has set the clock since boot.
+pub const MIN_VALID_TIME: Duration = Duration::from_secs(1_704_067_200);
+
+/// Whether the system clock can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncStatus {
+    /// The clock was never set, e.g. still counting from 1970 after boot.
+    Unset,
+    /// The clock was set, e.g. from an RTC, but is not kept in sync.
+    Unsynchronized,
+    /// A time synchronization daemon keeps the clock in sync.
+    Synchronized,
+}
+
+impl SyncStatus {
+    /// Classify a time and the kernel synchronization flag.
+    ///
+    /// # Arguments
+    /// * `now` - The current time.
+    /// * `kernel_synced` - Whether the kernel reports the clock as synchronized.
+    ///
+    /// # Returns
+    /// * `SyncStatus` - [`SyncStatus::Unset`] before [`MIN_VALID_TIME`] whatever the kernel says.
+    pub fn classify(now: SystemTime, kernel_synced: bool) -> Self {
+        let since_epoch = now.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        match (since_epoch >= MIN_VALID_TIME, kernel_synced) {
+            (false, _) => SyncStatus::Unset,
+            (true, false) => SyncStatus::Unsynchronized,
+            (true, true) => SyncStatus::Synchronized,
+        }
+    }
+}
+
+/// Get the status of the system clock.
+///
+/// # Returns
+/// * `Result<SyncStatus, Box<dyn Error>>` - The status, or an error if the kernel cannot be queried.
+pub fn status() -> Result<SyncStatus, Box<dyn Error>> {
+    let synced = is_kernel_synchronized()?;
+    Ok(SyncStatus::classify(SystemTime::now(), synced))
+}
+
+/// Ask the kernel whether the clock is synchronized, i.e. `STA_UNSYNC` is clear.
+///
+/// # Returns
+/// * `Result<bool, Box<dyn Error>>` - Whether the clock is synchronized, or an error if `adjtimex` fails.
+pub fn is_kernel_synchronized() -> Result<bool, Box<dyn Error>> {
+    // SAFETY: the timex is zero-initialised, so modes is 0 and the call only reads the state into it.
+    let mut timex: libc::timex = unsafe { mem::zeroed() };
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    if state < 0 {
+        return Err(format!("Cannot read the clock status: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(state != libc::TIME_ERROR && timex.status & libc::STA_UNSYNC == 0)
+}
+
+/// Set the system clock, which needs `CAP_SYS_TIME`.
+///
+/// # Arguments
+/// * `time` - The new time.
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if the time is before the epoch or the clock cannot be set.
+pub fn set_time(time: SystemTime) -> Result<(), Box<dyn Error>> {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|_| "Cannot set the clock before 1970")?;
+
+    let ts = libc::timespec {
+        tv_sec: since_epoch.as_secs() as libc::time_t,
+        tv_nsec: since_epoch.subsec_nanos() as _,
+    };
+    // SAFETY: the timespec outlives the call, which only reads it.
+    if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &ts) } != 0 {
+        return Err(format!("Cannot set the clock: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(())
+}
+
+/// Move the system clock at once by an offset, which needs `CAP_SYS_TIME`.
+///
+/// # Arguments
+/// * `offset` - The correction in nanoseconds, positive to move the clock forward.
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if the clock cannot be read or set.
+pub fn step(offset: i64) -> Result<(), Box<dyn Error>> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: the timespec is valid for writes for the duration of the call.
+    if unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut ts) } != 0 {
+        return Err(format!("Cannot read the clock: {}", io::Error::last_os_error()).into());
+    }
+
+    let nanos = ts.tv_sec as i128 * 1_000_000_000 + ts.tv_nsec as i128 + offset as i128;
+    let ts = libc::timespec {
+        tv_sec: nanos.div_euclid(1_000_000_000) as libc::time_t,
+        tv_nsec: nanos.rem_euclid(1_000_000_000) as _,
+    };
+    // SAFETY: the timespec outlives the call, which only reads it.
+    if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &ts) } != 0 {
+        return Err(format!("Cannot set the clock: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(())
+}
+
+/// Correct the system clock gradually by an offset, speeding it up or slowing
+/// it down by at most 0.5 ms per second, which needs `CAP_SYS_TIME`.
+///
+/// # Arguments
+/// * `offset` - The correction in nanoseconds, positive to move the clock forward.
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if the adjustment is refused.
+pub fn slew(offset: i64) -> Result<(), Box<dyn Error>> {
+    let micros = offset / 1000;
+    let delta = libc::timeval {
+        tv_sec: micros.div_euclid(1_000_000) as libc::time_t,
+        tv_usec: micros.rem_euclid(1_000_000) as libc::suseconds_t,
+    };
+    // SAFETY: the timeval outlives the call and the old delta is not requested.
+    if unsafe { libc::adjtime(&delta, std::ptr::null_mut()) } != 0 {
+        return Err(format!("Cannot slew the clock: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(())
+}
+
+/// Tell the kernel the clock was just synchronized, clearing `STA_UNSYNC` so
+/// [`status`] reports [`SyncStatus::Synchronized`]. The kernel sets the flag
+/// again when the error grows past 16 s without a new synchronization.
+///
+/// # Arguments
+/// * `error` - The estimated error of the clock.
+///
+/// # Returns
+/// * `Result<(), Box<dyn Error>>` - An error if the status cannot be changed.
+pub fn mark_synchronized(error: Duration) -> Result<(), Box<dyn Error>> {
+    // SAFETY: the timex is zero-initialised, so modes is 0 and the call only reads the state into it.
+    let mut timex: libc::timex = unsafe { mem::zeroed() };
+    if unsafe { libc::adjtimex(&mut timex) } < 0 {
+        return Err(format!("Cannot read the clock status: {}", io::Error::last_os_error()).into());
+    }
+
+    let micros = error.as_micros().min(i32::MAX as u128) as _;
+    timex.modes = libc::ADJ_STATUS | libc::ADJ_MAXERROR | libc::ADJ_ESTERROR;
+    timex.status &= !libc::STA_UNSYNC;
+    timex.maxerror = micros;
+    timex.esterror = micros;
+    // SAFETY: the timex is initialised by the previous call and only the fields selected by modes are applied.
+    if unsafe { libc::adjtimex(&mut timex) } < 0 {
+        return Err(format!("Cannot mark the clock synchronized: {}", io::Error::last_os_error()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn classify_before_the_minimum_time_is_unset() {
+        let min = MIN_VALID_TIME.as_secs();
+        for synced in [false, true] {
+            assert_eq!(SyncStatus::classify(SystemTime::UNIX_EPOCH, synced), SyncStatus::Unset);
+            assert_eq!(SyncStatus::classify(at(min - 1), synced), SyncStatus::Unset);
+            // before the epoch counts as unset too
+            assert_eq!(SyncStatus::classify(SystemTime::UNIX_EPOCH - Duration::from_secs(1), synced), SyncStatus::Unset);
+        }
+    }
+
+    #[test]
+    fn classify_follows_the_kernel_flag_from_the_minimum_time() {
+        for secs in [MIN_VALID_TIME.as_secs(), 1_792_195_200] {
+            assert_eq!(SyncStatus::classify(at(secs), false), SyncStatus::Unsynchronized);
+            assert_eq!(SyncStatus::classify(at(secs), true), SyncStatus::Synchronized);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::error::Error;
+
+use crate::{
+    font::Glyph,
+    multiplex::{Frame, DIGITS},
+    seven_segment::Segment,
+};
+
+/// Frame shown when a value does not fit on the display.
+pub const OVERFLOW: Frame = [Glyph::MINUS; DIGITS];
+
+/// Render an integer, right-aligned and padded with blanks.
+///
+/// # Arguments
+/// * `value` - The number to render, from -999 to 9999.
+///
+/// # Returns
+/// * `Frame` - The rendered digits, or [`OVERFLOW`] if the number does not fit.
+pub fn number(value: i32) -> Frame {
+    right_aligned(value < 0, &value.unsigned_abs().to_string(), 0)
+}
+
+/// Render a number with a fixed count of decimals, right-aligned.
+///
+/// The decimal point of the last integer digit is lit, with a leading zero
+/// for values below one (`0.5` renders as ` 0.5`).
+///
+/// # Arguments
+/// * `value` - The number to render.
+/// * `decimals` - The count of digits after the decimal point, up to 3.
+///
+/// # Returns
+/// * `Result<Frame, Box<dyn Error>>` - The rendered digits, [`OVERFLOW`] if the number does not fit,
+///   or an error if there are too many decimals.
+pub fn fixed(value: f32, decimals: u8) -> Result<Frame, Box<dyn Error>> {
+
+    if decimals as usize >= DIGITS {
+        return Err(format!("Cannot show {} decimals on {} digits", decimals, DIGITS).into());
+    }
+
+    let scaled = (value as f64 * 10f64.powi(decimals as i32)).round();
+    if !scaled.is_finite() || scaled.abs() >= 10f64.powi(DIGITS as i32) {
+        return Ok(OVERFLOW);
+    }
+
+    let magnitude = scaled.abs() as u32;
+    let digits = format!("{:0width$}", magnitude, width = decimals as usize + 1);
+
+    Ok(right_aligned(magnitude != 0 && scaled < 0.0, &digits, decimals))
+}
+
+/// Render a text, left-aligned and padded with blanks.
+///
+/// A `.` lights the decimal point of the preceding character, so `"12.34"`
+/// fits on four digits.
+///
+/// # Arguments
+/// * `text` - The text to render, see [`Glyph::from_char`] for the supported characters.
+///
+/// # Returns
+/// * `Result<Frame, Box<dyn Error>>` - The rendered digits, or an error if a character
+///   cannot be shown or the text is too long.
+pub fn string(text: &str) -> Result<Frame, Box<dyn Error>> {
+
+    let mut glyphs: Vec<Glyph> = Vec::with_capacity(DIGITS);
+
+    for c in text.chars() {
+        match (c, glyphs.last_mut()) {
+            ('.', Some(last)) if !last.contains(Segment::DP) => *last = last.with_dp(true),
+            ('.', _) => glyphs.push(Glyph::BLANK.with_dp(true)),
+            _ => glyphs.push(
+                Glyph::from_char(c)
+                    .ok_or_else(|| format!("Character {:?} cannot be shown on a seven-segment display", c))?,
+            ),
+        }
+    }
+
+    if glyphs.len() > DIGITS {
+        return Err(format!("Text {:?} does not fit on {} digits", text, DIGITS).into());
+    }
+
+    let mut frame = [Glyph::BLANK; DIGITS];
+    frame[..glyphs.len()].copy_from_slice(&glyphs);
+
+    Ok(frame)
+}
+
+/// Right-align decimal digits with an optional minus sign and decimal point.
+fn right_aligned(negative: bool, digits: &str, decimals: u8) -> Frame {
+
+    let width = digits.len() + negative as usize;
+    if width > DIGITS {
+        return OVERFLOW;
+    }
+
+    let mut frame = [Glyph::BLANK; DIGITS];
+    let start = DIGITS - digits.len();
+
+    if negative {
+        frame[start - 1] = Glyph::MINUS;
+    }
+    for (i, c) in digits.chars().enumerate() {
+        frame[start + i] = Glyph::from_char(c).unwrap_or(Glyph::BLANK);
+    }
+    if decimals > 0 {
+        let point = DIGITS - 1 - decimals as usize;
+        frame[point] = frame[point].with_dp(true);
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(c: char) -> Glyph {
+        Glyph::from_char(c).unwrap()
+    }
+
+    #[test]
+    fn string_lights_the_decimal_points() {
+        assert_eq!(string("12.34").unwrap(), [glyph('1'), glyph('2').with_dp(true), glyph('3'), glyph('4')]);
+        assert_eq!(string("Hi").unwrap(), [glyph('H'), glyph('i'), Glyph::BLANK, Glyph::BLANK]);
+        // a leading or doubled dot takes a digit of its own
+        assert_eq!(string(".5").unwrap(), [Glyph::BLANK.with_dp(true), glyph('5'), Glyph::BLANK, Glyph::BLANK]);
+        assert_eq!(string("1..").unwrap(), [glyph('1').with_dp(true), Glyph::BLANK.with_dp(true), Glyph::BLANK, Glyph::BLANK]);
+        assert_eq!(string("").unwrap(), [Glyph::BLANK; DIGITS]);
+    }
+
+    #[test]
+    fn string_rejects_unsupported_and_long_text() {
+        let error = string("MAX").unwrap_err();
+        assert_eq!(error.to_string(), "Character 'M' cannot be shown on a seven-segment display");
+        assert!(string("1.2?").is_err());
+        assert!(string("12345").is_err());
+        assert!(string("1.2.3.4.5").is_err());
+    }
+
+    #[test]
+    fn number_is_right_aligned() {
+        assert_eq!(number(0), string("   0").unwrap());
+        assert_eq!(number(42), string("  42").unwrap());
+        assert_eq!(number(9999), string("9999").unwrap());
+        assert_eq!(number(-7), string("  -7").unwrap());
+        assert_eq!(number(-999), string("-999").unwrap());
+    }
+
+    #[test]
+    fn number_overflow() {
+        for value in [10_000, -1_000, i32::MAX, i32::MIN] {
+            assert_eq!(number(value), OVERFLOW, "{}", value);
+        }
+        assert_eq!(OVERFLOW, string("----").unwrap());
+    }
+
+    #[test]
+    fn fixed_places_the_decimal_point() {
+        assert_eq!(fixed(3.256, 2).unwrap(), string(" 3.26").unwrap());
+        assert_eq!(fixed(0.5, 1).unwrap(), string("  0.5").unwrap());
+        assert_eq!(fixed(0.05, 3).unwrap(), string("0.050").unwrap());
+        assert_eq!(fixed(21.0, 0).unwrap(), string("  21").unwrap());
+        assert_eq!(fixed(-1.25, 1).unwrap(), string(" -1.3").unwrap());
+        assert_eq!(fixed(999.95, 1).unwrap(), OVERFLOW);
+    }
+
+    #[test]
+    fn fixed_negative_values() {
+        assert_eq!(fixed(-12.5, 1).unwrap(), string("-12.5").unwrap());
+        // rounds to zero, no minus sign left
+        assert_eq!(fixed(-0.01, 1).unwrap(), string("  0.0").unwrap());
+        assert_eq!(fixed(-100.0, 1).unwrap(), OVERFLOW);
+        assert_eq!(fixed(f32::NAN, 1).unwrap(), OVERFLOW);
+        assert_eq!(fixed(f32::NEG_INFINITY, 0).unwrap(), OVERFLOW);
+    }
+
+    #[test]
+    fn fixed_rejects_too_many_decimals() {
+        assert!(fixed(0.0, 4).is_err());
+    }
+}
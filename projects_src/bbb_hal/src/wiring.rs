@@ -0,0 +1,352 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    header::{self, HeaderPin},
+    multiplex::Digits,
+    seven_segment::Segment,
+};
+
+/// Path the binaries read the wiring from when no `--wiring` file is given.
+pub const DEFAULT_PATH: &str = "/etc/bbb-hal/wiring.toml";
+
+/// Electrical level that turns a segment on or enables a digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Polarity {
+    /// The line is driven high to turn the segment or digit on.
+    ActiveHigh,
+    /// The line is driven low to turn the segment or digit on.
+    ActiveLow,
+}
+
+impl Polarity {
+    /// Whether the lines have to be requested with the active-low flag.
+    pub fn is_active_low(self) -> bool {
+        self == Polarity::ActiveLow
+    }
+}
+
+/// Construction of a seven-segment module, setting the default polarities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisplayType {
+    /// Segment anodes on the GPIOs, digits enabled by pulling their common cathode low.
+    CommonCathode,
+    /// Segment cathodes on the GPIOs, digits enabled by driving their common anode high.
+    CommonAnode,
+}
+
+impl DisplayType {
+    /// Get the polarities of a display driven directly from the GPIOs.
+    ///
+    /// # Returns
+    /// * `(Polarity, Polarity)` - The segment and digit polarities.
+    pub fn polarities(self) -> (Polarity, Polarity) {
+        match self {
+            DisplayType::CommonCathode => (Polarity::ActiveHigh, Polarity::ActiveLow),
+            DisplayType::CommonAnode => (Polarity::ActiveLow, Polarity::ActiveHigh),
+        }
+    }
+}
+
+/// Wiring file as written by the user, every entry being optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WiringFile {
+    #[serde(default)]
+    segments: HashMap<String, String>,
+    #[serde(default)]
+    digits: HashMap<String, String>,
+    #[serde(default)]
+    polarity: PolarityFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolarityFile {
+    display: Option<DisplayType>,
+    segments: Option<Polarity>,
+    digits: Option<Polarity>,
+}
+
+/// Assignment of the display segments and digits to header pins.
+///
+/// The default matches the wiring of the original boards; a TOML file can
+/// override any part of it:
+///
+/// ```toml
+/// [segments]
+/// a = "P8_7"
+/// dp = "P8_10"
+///
+/// [digits]
+/// d1 = "P9_12"
+///
+/// [polarity]
+/// display = "common-anode"
+/// # PNP transistors on the digit anodes invert the digit logic again.
+/// digits = "active-low"
+/// ```
+///
+/// # Fields
+/// * `segments` - The header pin of every segment.
+/// * `digits` - The header pin of every digit selector.
+/// * `segment_polarity` - The level turning a segment on.
+/// * `digit_polarity` - The level enabling a digit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wiring {
+    segments: HashMap<Segment, HeaderPin>,
+    digits: HashMap<Digits, HeaderPin>,
+    pub segment_polarity: Polarity,
+    pub digit_polarity: Polarity,
+}
+
+impl Default for Wiring {
+    fn default() -> Self {
+        let segments = HashMap::from([
+            (Segment::A, HeaderPin::P8_7),
+            (Segment::B, HeaderPin::P8_8),
+            (Segment::C, HeaderPin::P8_9),
+            (Segment::DP, HeaderPin::P8_10),
+            (Segment::D, HeaderPin::P8_11),
+            (Segment::E, HeaderPin::P8_12),
+            (Segment::F, HeaderPin::P8_14),
+            (Segment::G, HeaderPin::P8_16),
+        ]);
+
+        let digits = HashMap::from([
+            (Digits::D_1, HeaderPin::P9_12), // display pin 12
+            (Digits::D_2, HeaderPin::P9_15), // display pin 9
+            (Digits::D_3, HeaderPin::P9_23), // display pin 8
+            (Digits::D_4, HeaderPin::P9_27), // display pin 6
+        ]);
+
+        let (segment_polarity, digit_polarity) = DisplayType::CommonCathode.polarities();
+
+        Self {
+            segments,
+            digits,
+            segment_polarity,
+            digit_polarity,
+        }
+    }
+}
+
+impl Wiring {
+    /// Load the wiring from a TOML file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the wiring file.
+    ///
+    /// # Returns
+    /// * `Result<Wiring, Box<dyn Error>>` - The default wiring with the entries of the file applied.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read wiring file {}: {}", path.display(), e))?;
+
+        Self::from_toml(&text)
+            .map_err(|e| format!("Invalid wiring file {}: {}", path.display(), e).into())
+    }
+
+    /// Load the wiring given on the command line, falling back to [`DEFAULT_PATH`]
+    /// and then to the compiled-in default.
+    ///
+    /// # Arguments
+    /// * `path` - The path passed with `--wiring`, if any.
+    ///
+    /// # Returns
+    /// * `Result<Wiring, Box<dyn Error>>` - The wiring to use.
+    pub fn load_or_default(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        match path {
+            Some(path) => Self::load(path),
+            None if Path::new(DEFAULT_PATH).exists() => Self::load(DEFAULT_PATH),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Parse the wiring from the content of a TOML file.
+    ///
+    /// # Arguments
+    /// * `text` - The TOML document.
+    ///
+    /// # Returns
+    /// * `Result<Wiring, Box<dyn Error>>` - The default wiring with the entries of the document applied.
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+
+        let file: WiringFile = toml::from_str(text)?;
+        let mut wiring = Self::default();
+
+        for (name, pin) in &file.segments {
+            let seg = Segment::ALL
+                .into_iter()
+                .find(|seg| format!("{:?}", seg).eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Unknown segment: {}", name))?;
+            wiring.segments.insert(seg, pin.parse()?);
+        }
+
+        for (name, pin) in &file.digits {
+            let dig = Digits::ALL
+                .into_iter()
+                .find(|dig| format!("{:?}", dig).replace('_', "").eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Unknown digit: {}", name))?;
+            wiring.digits.insert(dig, pin.parse()?);
+        }
+
+        if let Some(display) = file.polarity.display {
+            wiring.set_display_type(display);
+        }
+        if let Some(polarity) = file.polarity.segments {
+            wiring.segment_polarity = polarity;
+        }
+        if let Some(polarity) = file.polarity.digits {
+            wiring.digit_polarity = polarity;
+        }
+
+        wiring.validate()?;
+
+        Ok(wiring)
+    }
+
+    /// Get the header pin a segment is wired to.
+    pub fn segment(&self, seg: Segment) -> HeaderPin {
+        self.segments[&seg]
+    }
+
+    /// Get the header pin a digit selector is wired to.
+    pub fn digit(&self, dig: Digits) -> HeaderPin {
+        self.digits[&dig]
+    }
+
+    /// Wire a segment to another header pin.
+    pub fn set_segment(&mut self, seg: Segment, pin: HeaderPin) {
+        self.segments.insert(seg, pin);
+    }
+
+    /// Wire a digit selector to another header pin.
+    pub fn set_digit(&mut self, dig: Digits, pin: HeaderPin) {
+        self.digits.insert(dig, pin);
+    }
+
+    /// Use the default polarities of a display type for segments and digits.
+    pub fn set_display_type(&mut self, display: DisplayType) {
+        (self.segment_polarity, self.digit_polarity) = display.polarities();
+    }
+
+    /// Get the header pins of all segments, in [`Segment::ALL`] order.
+    pub fn segment_pins(&self) -> Vec<HeaderPin> {
+        Segment::ALL.iter().map(|&seg| self.segment(seg)).collect()
+    }
+
+    /// Get the header pins of all digit selectors, in [`Digits::ALL`] order.
+    pub fn digit_pins(&self) -> Vec<HeaderPin> {
+        Digits::ALL.iter().map(|&dig| self.digit(dig)).collect()
+    }
+
+    /// Check that no pin is used twice or claimed by an on-board peripheral.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error describing the first invalid pin.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut pins = self.segment_pins();
+        pins.extend(self.digit_pins());
+        header::validate(&pins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_names_ignore_case() {
+        let wiring = Wiring::from_toml("[segments]\nA = \"P8_17\"\nDp = \"P8_18\"\n").unwrap();
+        assert_eq!(wiring.segment(Segment::A), HeaderPin::P8_17);
+        assert_eq!(wiring.segment(Segment::DP), HeaderPin::P8_18);
+        assert_eq!(
+            wiring.segment(Segment::B),
+            Wiring::default().segment(Segment::B)
+        );
+    }
+
+    #[test]
+    fn digit_keys() {
+        let wiring = Wiring::from_toml("[digits]\nd1 = \"P9_11\"\nD4 = \"p9_13\"\n").unwrap();
+        assert_eq!(wiring.digit(Digits::D_1), HeaderPin::P9_11);
+        assert_eq!(
+            wiring.digit(Digits::D_2),
+            Wiring::default().digit(Digits::D_2)
+        );
+        assert_eq!(wiring.digit(Digits::D_4), HeaderPin::P9_13);
+
+        assert!(Wiring::from_toml("[digits]\nd5 = \"P9_11\"\n").is_err());
+        assert!(Wiring::from_toml("[digits]\nd_1 = \"P9_11\"\n").is_err());
+    }
+
+    #[test]
+    fn display_type_then_overrides() {
+        let wiring = Wiring::from_toml("[polarity]\ndisplay = \"common-anode\"\n").unwrap();
+        assert_eq!(
+            (wiring.segment_polarity, wiring.digit_polarity),
+            (Polarity::ActiveLow, Polarity::ActiveHigh)
+        );
+
+        // the overrides apply on top of the display type, whatever their order in the file
+        let wiring =
+            Wiring::from_toml("[polarity]\ndigits = \"active-low\"\ndisplay = \"common-anode\"\n")
+                .unwrap();
+        assert_eq!(
+            (wiring.segment_polarity, wiring.digit_polarity),
+            (Polarity::ActiveLow, Polarity::ActiveLow)
+        );
+
+        let wiring = Wiring::from_toml("[polarity]\nsegments = \"active-low\"\n").unwrap();
+        assert_eq!(
+            (wiring.segment_polarity, wiring.digit_polarity),
+            (Polarity::ActiveLow, Polarity::ActiveLow)
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for text in [
+            "[segments]\nh = \"P8_17\"\n",
+            "[segment]\na = \"P8_17\"\n",
+            "[polarity]\ncommon = \"active-low\"\n",
+            "[polarity]\ndisplay = \"common-ground\"\n",
+            "[segments]\na = \"P8_1\"\n",
+        ] {
+            assert!(Wiring::from_toml(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn duplicate_pins_are_rejected() {
+        // P8_8 is segment B by default
+        let error = Wiring::from_toml("[segments]\na = \"P8_8\"\n").unwrap_err();
+        assert_eq!(error.to_string(), "Pin P8_8 is assigned more than once");
+
+        let error = Wiring::from_toml("[digits]\nd2 = \"P8_7\"\n").unwrap_err();
+        assert_eq!(error.to_string(), "Pin P8_7 is assigned more than once");
+    }
+
+    #[test]
+    fn pins_of_on_board_peripherals_are_rejected() {
+        let error = Wiring::from_toml("[segments]\na = \"P8_3\"\n").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Pin P8_3 is used by the on-board"),
+            "{}",
+            error
+        );
+    }
+}
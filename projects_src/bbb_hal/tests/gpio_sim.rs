@@ -0,0 +1,120 @@
+//! Chip lookup against simulated AM335x GPIO banks.
+//!
+//! Needs root and the `gpio-sim` module (`modprobe gpio-sim`), and a machine
+//! without real AM335x banks, i.e. not the board itself:
+//!
+//! ```text
+//! sudo -E cargo test -p bbb-hal --test gpio_sim -- --ignored
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bbb_hal::{
+    gpio::{chip::ChipMap, CdevBackend, GpioBackend},
+    header::HeaderPin,
+    Value,
+};
+
+const CONFIGFS: &str = "/sys/kernel/config/gpio-sim";
+
+/// Bank labels, mixing the legacy range and the controller address forms.
+const LABELS: [&str; 4] = ["gpio-0-31", "4804c000.gpio", "gpio-64-95", "481ae000.gpio"];
+
+/// Header pins named on the simulated lines, as the BeagleBone device tree does.
+const NAMED: [HeaderPin; 5] = [HeaderPin::P9_11, HeaderPin::P8_26, HeaderPin::P9_12, HeaderPin::P8_7, HeaderPin::P9_27];
+
+/// Name of a header pin in the BeagleBone device tree, e.g. `P8_07`.
+fn line_name(pin: HeaderPin) -> String {
+    let name = pin.to_string();
+    let (header, number) = name.split_once('_').unwrap();
+    format!("{}_{:02}", header, number.parse::<u32>().unwrap())
+}
+
+/// A live gpio-sim device with the four banks, removed on drop.
+struct SimDevice {
+    dir: PathBuf,
+}
+
+impl SimDevice {
+    fn create() -> Self {
+        let dir = Path::new(CONFIGFS).join(format!("bbb-hal-test-{}", std::process::id()));
+        fs::create_dir(&dir).expect("gpio-sim configfs not available, run as root after modprobe gpio-sim");
+        let device = Self { dir };
+
+        for (bank, label) in LABELS.iter().enumerate() {
+            let bank_dir = device.dir.join(format!("bank{}", bank));
+            fs::create_dir(&bank_dir).unwrap();
+            fs::write(bank_dir.join("label"), label).unwrap();
+            fs::write(bank_dir.join("num_lines"), "32").unwrap();
+
+            for pin in NAMED.iter().filter(|pin| pin.info().bank as usize == bank) {
+                let line_dir = bank_dir.join(format!("line{}", pin.info().offset));
+                fs::create_dir(&line_dir).unwrap();
+                fs::write(line_dir.join("name"), line_name(*pin)).unwrap();
+            }
+        }
+        fs::write(device.dir.join("live"), "1").unwrap();
+
+        device
+    }
+
+    /// Value the simulated chip sees on a line.
+    fn value(&self, pin: HeaderPin) -> String {
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap().trim().to_string();
+        let dev_name = read(self.dir.join("dev_name"));
+        let chip_name = read(self.dir.join(format!("bank{}", pin.info().bank)).join("chip_name"));
+        let line = Path::new("/sys/devices/platform")
+            .join(dev_name)
+            .join(chip_name)
+            .join(format!("sim_gpio{}", pin.info().offset));
+        read(line.join("value"))
+    }
+}
+
+impl Drop for SimDevice {
+    fn drop(&mut self) {
+        let _ = fs::write(self.dir.join("live"), "0");
+        for bank in 0..LABELS.len() {
+            let bank_dir = self.dir.join(format!("bank{}", bank));
+            for pin in NAMED {
+                let _ = fs::remove_dir(bank_dir.join(format!("line{}", pin.info().offset)));
+            }
+            let _ = fs::remove_dir(bank_dir);
+        }
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+#[test]
+#[ignore = "needs root and the gpio-sim module"]
+fn header_pins_resolve_through_the_bank_labels() {
+    let sim = SimDevice::create();
+    let chips = ChipMap::discover().unwrap();
+
+    for (bank, label) in LABELS.iter().enumerate() {
+        let chip = chips.chips().iter().find(|chip| chip.label == *label).unwrap();
+        assert_eq!(chip.bank, Some(bank as u32), "{}", label);
+    }
+
+    for pin in NAMED {
+        let gpio = chips.find_named(&line_name(pin)).unwrap();
+        assert_eq!(gpio, pin.gpio(), "{:?}", pin);
+
+        let (path, offset) = chips.locate(gpio).unwrap();
+        let chip = chips.chips().iter().find(|chip| chip.path == path).unwrap();
+        assert_eq!((chip.label.as_str(), offset), (LABELS[pin.info().bank as usize], pin.info().offset), "{:?}", pin);
+    }
+
+    // the lines driven through the backend are the ones the banks see
+    let pins = NAMED.map(HeaderPin::gpio);
+    let mut backend = CdevBackend::with_chips(chips);
+    backend.request_outputs(&pins, Value::Inactive, false).unwrap();
+    backend.set_line(HeaderPin::P9_12.gpio(), Value::Active).unwrap();
+    for pin in NAMED {
+        let expected = if pin == HeaderPin::P9_12 { "1" } else { "0" };
+        assert_eq!(sim.value(pin), expected, "{:?}", pin);
+    }
+}
@@ -1,6 +1,5 @@
-use crate::seven_segment::SevenSegmentDisplay;
+use bbb_hal::{pwm::PwmChannel, seven_segment::SevenSegmentDisplay, shutdown::Shutdown, wiring::Wiring};
 use rand::Rng;
-use std::thread;
 use std::time::Duration;
 
 pub mod config{
@@ -12,24 +11,77 @@ pub mod config{
 /// 
 /// # Fields
 /// * `display` - A reference to the SevenSegmentDisplay struct.
+/// * `shutdown` - Stops the counting loops when requested.
 /// 
 /// # Examples
 /// ```
-/// let mut counter = counter::Counter::new()?;
+/// let mut counter = counter::Counter::new(Wiring::default(), Shutdown::install()?)?;
 /// counter.count_up(1000)?;
 /// ```
 pub struct Counter {
     display: SevenSegmentDisplay,
+    shutdown: Shutdown,
 }
 
 impl Counter {
     /// Create a new instance of the Counter struct.
     /// 
+    /// # Arguments
+    /// * `wiring` - The header pins and polarity of the display segments.
+    /// * `shutdown` - Stops the counting loops when requested.
+    /// 
+    /// # Returns
+    /// * `Counter` - A new instance of the Counter struct.
+    pub fn new(wiring: Wiring, shutdown: Shutdown) -> Result<Self, Box<dyn std::error::Error>> {
+        let display = SevenSegmentDisplay::with_wiring(wiring)?;
+        Ok(Self::with_display(display, shutdown))
+    }
+
+    /// Create a counter on an already constructed display, e.g. one backed by a `MockBackend`.
+    /// 
+    /// # Arguments
+    /// * `display` - The display to count on.
+    /// * `shutdown` - Stops the counting loops when requested.
+    /// 
     /// # Returns
     /// * `Counter` - A new instance of the Counter struct.
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let display = SevenSegmentDisplay::new()?;
-        Ok(Self { display })
+    pub fn with_display(display: SevenSegmentDisplay, shutdown: Shutdown) -> Self {
+        Self { display, shutdown }
+    }
+
+    /// Dim the display.
+    /// 
+    /// # Arguments
+    /// * `level` - The brightness level, from 0 (off) to 15.
+    /// 
+    /// # Returns
+    /// * `Result<(), Box<dyn std::error::Error>>` - An error if the level is out of range.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.display.set_brightness(level)
+    }
+
+    /// Dim the display with a hardware PWM channel instead of a software PWM thread.
+    /// 
+    /// # Arguments
+    /// * `channel` - The PWM channel gating the common line of the display.
+    /// 
+    /// # Returns
+    /// * `Result<(), Box<dyn std::error::Error>>` - An error if the channel cannot be configured.
+    pub fn set_hardware_pwm(&mut self, channel: PwmChannel) -> Result<(), Box<dyn std::error::Error>> {
+        self.display.set_hardware_pwm(channel)
+    }
+
+    /// Show a digit for some time.
+    /// 
+    /// # Arguments
+    /// * `digit` - The digit to show.
+    /// * `delay` - The time to show it, in ms.
+    /// 
+    /// # Returns
+    /// * `Result<bool, Box<dyn std::error::Error>>` - `false` if a shutdown was requested meanwhile.
+    fn show(&mut self, digit: u8, delay: u64) -> Result<bool, Box<dyn std::error::Error>> {
+        self.display.set_digit(digit)?;
+        Ok(self.shutdown.sleep(Duration::from_millis(delay)))
     }
 
     /// Count up from 0 to 10 (inclusive).
@@ -38,12 +90,13 @@ impl Counter {
     /// * `delay` - The time delay between each count.
     /// 
     /// # Returns
-    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure.
+    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure, `Ok` once a shutdown is requested.
     pub fn count_up(&mut self, delay: u64) -> Result<(), Box<dyn std::error::Error>> {
         loop {
             for i in config::MIN_DIGIT..=config::MAX_DIGIT {
-                self.display.set_digit(i)?;
-                thread::sleep(Duration::from_millis(delay));
+                if !self.show(i, delay)? {
+                    return Ok(());
+                }
             }
         }
     }
@@ -54,13 +107,14 @@ impl Counter {
     /// * `delay` - The time delay between each count.
     /// 
     /// # Returns
-    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure.
+    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure, `Ok` once a shutdown is requested.
     pub fn count_down(&mut self, delay: u64) -> Result<(), Box<dyn std::error::Error>> {
         
         loop {
             for i in (config::MIN_DIGIT..=config::MAX_DIGIT).rev() {
-                self.display.set_digit(i)?;
-                thread::sleep(Duration::from_millis(delay));
+                if !self.show(i, delay)? {
+                    return Ok(());
+                }
             }
         }
     }
@@ -71,17 +125,19 @@ impl Counter {
     /// * `delay` - The time delay between each count.
     /// 
     /// # Returns
-    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure.
+    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure, `Ok` once a shutdown is requested.
     pub fn count_updown(&mut self, delay: u64) -> Result<(), Box<dyn std::error::Error>> {
         
         loop {
             for i in config::MIN_DIGIT..=(config::MAX_DIGIT-1) {
-                    self.display.set_digit(i)?;
-                thread::sleep(Duration::from_millis(delay));
+                if !self.show(i, delay)? {
+                    return Ok(());
+                }
             }
             for i in ((config::MIN_DIGIT + 1)..=config::MAX_DIGIT).rev() {
-                self.display.set_digit(i)?;
-                thread::sleep(Duration::from_millis(delay));
+                if !self.show(i, delay)? {
+                    return Ok(());
+                }
             }
         }
     }
@@ -92,14 +148,78 @@ impl Counter {
     /// * `delay` - The time delay between each display.
     /// 
     /// # Returns
-    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure.
+    /// * `Result<(), Box<dyn std::error::Error>>` - A Result indicating success or failure, `Ok` once a shutdown is requested.
     pub fn count_random(&mut self, delay: u64) -> Result<(), Box<dyn std::error::Error>> {
          let mut rng = rand::rng();
         
         loop {
             let random_digit = rng.random_range(0..10);
-            self.display.set_digit(random_digit)?;
-            thread::sleep(Duration::from_millis(delay));
+            if !self.show(random_digit, delay)? {
+                return Ok(());
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use bbb_hal::{font::Glyph, gpio::mock::MockBackend, seven_segment::Segment, Value};
+
+    use super::*;
+
+    /// Run a counting loop until it showed some digits, and return the digits in order.
+    fn run<F>(count: usize, mut f: F) -> Vec<u8>
+    where
+        F: FnMut(&mut Counter) -> Result<(), Box<dyn std::error::Error>> + Send + 'static,
+    {
+        let mock = MockBackend::new();
+        let display = SevenSegmentDisplay::with_backend(Box::new(mock.clone()), Wiring::default()).unwrap();
+        let shutdown = Shutdown::new();
+        let mut counter = Counter::with_display(display, shutdown.clone());
+
+        let handle = thread::spawn(move || f(&mut counter).map_err(|e| e.to_string()));
+        while mock.writes().len() < count * 8 {
+            thread::sleep(Duration::from_millis(1));
         }
+        shutdown.request();
+        handle.join().unwrap().unwrap();
+
+        // every digit is one write of the 8 segment lines
+        let digits = (0..=15).map(|d| Glyph::from_digit(d).unwrap()).collect::<Vec<_>>();
+        mock.writes()
+            .chunks_exact(8)
+            .map(|lines| {
+                Segment::ALL
+                    .into_iter()
+                    .zip(lines)
+                    .fold(Glyph::BLANK, |glyph, (seg, (_, value))| glyph.with_segment(seg, *value == Value::Active))
+            })
+            .take(count)
+            .map(|glyph| digits.iter().position(|&d| d == glyph).unwrap() as u8)
+            .collect()
+    }
+
+    #[test]
+    fn count_up_wraps_after_ten() {
+        let expected = (0..=10).chain(0..=2).collect::<Vec<_>>();
+        assert_eq!(run(expected.len(), |c| c.count_up(1)), expected);
+    }
+
+    #[test]
+    fn count_down_wraps_after_zero() {
+        let expected = (0..=10).rev().chain((8..=10).rev()).collect::<Vec<_>>();
+        assert_eq!(run(expected.len(), |c| c.count_down(1)), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn count_updown_does_not_repeat_the_ends() {
+        let expected = (0..=9).chain((1..=10).rev()).chain(0..=1).collect::<Vec<_>>();
+        assert_eq!(run(expected.len(), |c| c.count_updown(1)), expected);
+    }
+
+    #[test]
+    fn count_random_stays_in_range() {
+        assert!(run(50, |c| c.count_random(0)).iter().all(|&d| d < 10));
+    }
+}
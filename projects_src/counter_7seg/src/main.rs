@@ -1,6 +1,12 @@
-use std::env;
+use std::{env, path::PathBuf};
+
+use bbb_hal::{
+    brightness::MAX_BRIGHTNESS,
+    pwm::{self, PwmChannel},
+    shutdown::Shutdown,
+    wiring::{self, Wiring},
+};
 
-mod seven_segment;
 mod counter;
 
 /// Organized variable configuration for the script.
@@ -16,17 +22,31 @@ pub mod config{
 ///  Result<(), Box<dyn Error>>
 fn main() -> Result<(), Box<dyn std::error::Error>> {
 
-    let args: Vec<String> = env::args().collect::<Vec<String>>();
+    let mut args: Vec<String> = env::args().collect::<Vec<String>>();
+    let wiring_path = take_option(&mut args, "--wiring")?.map(PathBuf::from);
+    let brightness: u8 = match take_option(&mut args, "--brightness")? {
+        Some(value) => value.parse().map_err(|_| format!("Invalid brightness: {}", value))?,
+        None => MAX_BRIGHTNESS,
+    };
+    let pwm_channel = take_option(&mut args, "--pwm")?;
 
     if args.len() != 3 {
-        let usage_txt: String = format!(r#"usage: {} <direction> <delay>
+        let usage_txt: String = format!(r#"usage: {} [--wiring <file>] [--brightness <level>] [--pwm <chip>:<channel>] <direction> <delay>
 Valid directions : up, down,updown,random
-Recommended delay range in ms : 0 to 1000"#, args[0]);
+Recommended delay range in ms : 0 to 1000
+Brightness from 0 to {max}, defaults to {max}
+PWM chip given by number or device name (e.g. 48302200.pwm:0), dims with hardware PWM
+Wiring file defaults to {}"#, args[0], wiring::DEFAULT_PATH, max = MAX_BRIGHTNESS);
         println!("{}", usage_txt);
         
     } else {
         let mut delay_value: u64 = args[2].parse().map_err(|_|"Delay value must be a number")?;
-        let mut counter = counter::Counter::new()?;
+        let wiring = Wiring::load_or_default(wiring_path.as_deref())?;
+        let mut counter = counter::Counter::new(wiring, Shutdown::install()?)?;
+        if let Some(spec) = pwm_channel {
+            counter.set_hardware_pwm(open_pwm(&spec)?)?;
+        }
+        counter.set_brightness(brightness)?;
         if delay_value > config::MAX_DELAY_MS {
             println!("Delay value must be less than or equal to {}", config::MAX_DELAY_MS);
             delay_value = config::DEFAULT_DELAY_MS;
@@ -50,4 +70,48 @@ Recommended delay range in ms : 0 to 1000"#, args[0]);
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Remove an option and its value from the argument list.
+/// 
+/// # Arguments
+/// * `args` - The command line arguments.
+/// * `name` - The option name, e.g. `--wiring`.
+/// 
+/// # Returns
+/// * `Result<Option<String>, Box<dyn Error>>` - The option value, if the option was given.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    
+    let Some(pos) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+
+    if pos + 1 >= args.len() {
+        return Err(format!("Missing value for {}", name).into());
+    }
+
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+
+    Ok(Some(value))
+}
+
+/// Open the PWM channel given with `--pwm`.
+/// 
+/// # Arguments
+/// * `spec` - The chip number or device name and the channel, e.g. `48302200.pwm:0`.
+/// 
+/// # Returns
+/// * `Result<PwmChannel, Box<dyn Error>>` - The exported channel.
+fn open_pwm(spec: &str) -> Result<PwmChannel, Box<dyn std::error::Error>> {
+
+    let (chip, channel) = spec
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Invalid PWM channel {}, expected <chip>:<channel>", spec))?;
+    let channel: u32 = channel.parse().map_err(|_| format!("Invalid PWM channel number: {}", channel))?;
+
+    match chip.parse() {
+        Ok(number) => PwmChannel::open(number, channel),
+        Err(_) => PwmChannel::open_device(pwm::SYSFS_ROOT, chip, channel),
+    }
+}
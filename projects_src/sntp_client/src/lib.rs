@@ -0,0 +1,419 @@
+//! Minimal SNTP client (RFC 4330) setting the system clock from a single
+//! NTP server, by default the USB Ethernet host at 192.168.7.1.
+//!
+//! A [`SntpClient`] queries the server for a [`Sample`] of the clock offset,
+//! and [`apply`] slews or steps the system clock by it and clears the kernel
+//! `STA_UNSYNC` flag, which is how the digital clock learns the time is set.
+//! [`server_reply`] builds the answer of a stand-in server for tests.
+
+use std::{
+    error::Error,
+    fmt, io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant, SystemTime},
+};
+
+use bbb_hal::system_clock;
+
+/// Server queried when none is given.
+pub const DEFAULT_SERVER: &str = "192.168.7.1";
+
+/// UDP port of NTP servers.
+pub const NTP_PORT: u16 = 123;
+
+/// Time waited for the reply of the server.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Offsets above this are stepped rather than slewed, as ntpd does.
+pub const DEFAULT_STEP_THRESHOLD: Duration = Duration::from_millis(128);
+
+/// Size of an NTP packet without extensions.
+const PACKET_LEN: usize = 48;
+
+/// Seconds from the NTP epoch, 1900-01-01, to the Unix epoch.
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Leap indicator 0, version 4, mode 3 (client).
+const CLIENT_HEADER: u8 = 0x23;
+
+/// Leap indicator 0, version 4, mode 4 (server).
+const SERVER_HEADER: u8 = 0x24;
+
+/// Offsets of the timestamps in a packet.
+const ORIGINATE: usize = 24;
+const RECEIVE: usize = 32;
+const TRANSMIT: usize = 40;
+
+/// Result of a query.
+///
+/// # Fields
+/// * `offset` - The nanoseconds the local clock has to move forward to match the server, negative if it is ahead.
+/// * `delay` - The round-trip delay to the server.
+/// * `stratum` - The stratum of the server, 1 for a reference clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub offset: i64,
+    pub delay: Duration,
+    pub stratum: u8,
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {:+.6} s, delay {:.3} ms, stratum {}",
+            self.offset as f64 / 1e9, self.delay.as_secs_f64() * 1e3, self.stratum)
+    }
+}
+
+/// How the clock was corrected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Adjustment {
+    /// Corrected gradually, the clock never jumps.
+    Slewed,
+    /// Set at once.
+    Stepped,
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adjustment::Slewed => write!(f, "slewed"),
+            Adjustment::Stepped => write!(f, "stepped"),
+        }
+    }
+}
+
+/// Client of a single NTP server.
+///
+/// # Fields
+/// * `socket` - The UDP socket the queries are sent from.
+/// * `server` - The address of the server.
+/// * `timeout` - The time waited for the reply of the server.
+///
+/// # Examples
+/// Query a stand-in server running 5 s ahead:
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{net::UdpSocket, thread, time::{Duration, SystemTime}};
+/// use sntp_client::{server_reply, SntpClient};
+///
+/// let server = UdpSocket::bind("127.0.0.1:0")?;
+/// let address = server.local_addr()?;
+/// thread::spawn(move || {
+///     let mut request = [0; 48];
+///     let (_, client) = server.recv_from(&mut request).unwrap();
+///     let now = SystemTime::now() + Duration::from_secs(5);
+///     server.send_to(&server_reply(&request, now, now), client).unwrap();
+/// });
+///
+/// let sample = SntpClient::new(&address.to_string())?.query()?;
+/// assert!((sample.offset - 5_000_000_000).abs() < 100_000_000);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SntpClient {
+    socket: UdpSocket,
+    server: SocketAddr,
+    timeout: Duration,
+}
+
+impl SntpClient {
+    /// Create a client of a server.
+    ///
+    /// # Arguments
+    /// * `server` - The host name or address of the server, with an optional port (default: [`NTP_PORT`]).
+    ///
+    /// # Returns
+    /// * `Result<SntpClient, Box<dyn Error>>` - The client, or an error if the server cannot be resolved.
+    pub fn new(server: &str) -> Result<Self, Box<dyn Error>> {
+
+        let with_port = match server.parse::<SocketAddr>() {
+            Ok(address) => address.to_string(),
+            Err(_) if server.contains(':') => server.to_string(),
+            Err(_) => format!("{}:{}", server, NTP_PORT),
+        };
+        let server = with_port
+            .to_socket_addrs()
+            .map_err(|e| format!("Cannot resolve NTP server {}: {}", server, e))?
+            .next()
+            .ok_or_else(|| format!("NTP server {} has no address", server))?;
+
+        let local = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local).map_err(|e| format!("Cannot open UDP socket: {}", e))?;
+
+        Ok(Self { socket, server, timeout: DEFAULT_TIMEOUT })
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Set the time waited for the reply of the server.
+    pub fn set_timeout(&mut self, timeout: Duration) -> Result<(), Box<dyn Error>> {
+        if timeout.is_zero() {
+            return Err("Invalid timeout: 0 ms".into());
+        }
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    /// Ask the server for the time.
+    ///
+    /// # Returns
+    /// * `Result<Sample, Box<dyn Error>>` - The offset of the local clock, or an error if the server
+    ///   does not answer in time or answers with an invalid or unsynchronized time.
+    pub fn query(&self) -> Result<Sample, Box<dyn Error>> {
+
+        let mut request = [0; PACKET_LEN];
+        request[0] = CLIENT_HEADER;
+        let sent = to_ntp(SystemTime::now());
+        request[TRANSMIT..TRANSMIT + 8].copy_from_slice(&sent.to_be_bytes());
+
+        self.socket
+            .send_to(&request, self.server)
+            .map_err(|e| format!("Cannot send to NTP server {}: {}", self.server, e))?;
+
+        // stray packets must not extend the wait past the timeout
+        let deadline = Instant::now() + self.timeout;
+        let no_reply = || format!("No reply from NTP server {}", self.server);
+
+        let mut reply = [0; PACKET_LEN];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(no_reply().into());
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+
+            let (len, from) = self.socket
+                .recv_from(&mut reply)
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => no_reply(),
+                    _ => format!("Cannot receive from NTP server {}: {}", self.server, e),
+                })?;
+            let received = to_ntp(SystemTime::now());
+
+            // stray packets and late replies to earlier queries are skipped
+            if from != self.server || len < PACKET_LEN || timestamp(&reply, ORIGINATE) != sent {
+                continue;
+            }
+
+            return sample(&reply, sent, received);
+        }
+    }
+}
+
+/// Check a reply and compute the offset from its four timestamps.
+fn sample(reply: &[u8; PACKET_LEN], sent: u64, received: u64) -> Result<Sample, Box<dyn Error>> {
+
+    let leap = reply[0] >> 6;
+    let mode = reply[0] & 0x07;
+    let stratum = reply[1];
+
+    if mode != 4 {
+        return Err(format!("NTP reply has mode {} instead of 4 (server)", mode).into());
+    }
+    if stratum == 0 {
+        let code = String::from_utf8_lossy(&reply[12..16]).into_owned();
+        return Err(format!("NTP server refused the query: {}", code.trim_end_matches('\0')).into());
+    }
+    if leap == 3 || stratum > 15 {
+        return Err("NTP server is not synchronized".into());
+    }
+
+    let server_received = timestamp(reply, RECEIVE);
+    let server_sent = timestamp(reply, TRANSMIT);
+    if server_sent == 0 {
+        return Err("NTP reply has no transmit time".into());
+    }
+
+    let [t1, t2, t3, t4] = [sent, server_received, server_sent, received].map(from_ntp);
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    let delay = ((t4 - t1) - (t3 - t2)).max(0);
+
+    Ok(Sample { offset: offset as i64, delay: Duration::from_nanos(delay as u64), stratum })
+}
+
+/// Build the reply of a stratum 1 server to a request, e.g. for a stand-in server in tests.
+///
+/// # Arguments
+/// * `request` - The request of the client.
+/// * `received` - The server time the request was received at.
+/// * `sent` - The server time the reply is sent at.
+///
+/// # Returns
+/// * `[u8; 48]` - The reply.
+pub fn server_reply(request: &[u8; PACKET_LEN], received: SystemTime, sent: SystemTime) -> [u8; PACKET_LEN] {
+    let mut reply = [0; PACKET_LEN];
+    reply[0] = SERVER_HEADER;
+    reply[1] = 1;
+    reply[12..16].copy_from_slice(b"LOCL");
+    reply[ORIGINATE..ORIGINATE + 8].copy_from_slice(&request[TRANSMIT..TRANSMIT + 8]);
+    reply[RECEIVE..RECEIVE + 8].copy_from_slice(&to_ntp(received).to_be_bytes());
+    reply[TRANSMIT..TRANSMIT + 8].copy_from_slice(&to_ntp(sent).to_be_bytes());
+    reply
+}
+
+/// Correct the system clock by the offset of a sample and mark it synchronized.
+///
+/// # Arguments
+/// * `sample` - The result of a query.
+/// * `step_threshold` - The offset above which the clock is stepped rather than slewed.
+///
+/// # Returns
+/// * `Result<Adjustment, Box<dyn Error>>` - How the clock was corrected, or an error if it cannot be set.
+pub fn apply(sample: &Sample, step_threshold: Duration) -> Result<Adjustment, Box<dyn Error>> {
+
+    let adjustment = if sample.offset.unsigned_abs() as u128 > step_threshold.as_nanos() {
+        system_clock::step(sample.offset)?;
+        Adjustment::Stepped
+    } else {
+        system_clock::slew(sample.offset)?;
+        Adjustment::Slewed
+    };
+
+    // half the round trip bounds the error of the server time
+    system_clock::mark_synchronized(sample.delay / 2)?;
+
+    Ok(adjustment)
+}
+
+fn timestamp(packet: &[u8; PACKET_LEN], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&packet[offset..offset + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Convert a time to an NTP timestamp: seconds since 1900 and a 32-bit fraction.
+fn to_ntp(time: SystemTime) -> u64 {
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    let secs = (since_epoch.as_secs() as i64 + NTP_UNIX_OFFSET) as u64 & 0xFFFF_FFFF;
+    let fraction = ((since_epoch.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    (secs << 32) | fraction
+}
+
+/// Convert an NTP timestamp to nanoseconds since the Unix epoch.
+///
+/// Seconds with the top bit clear are taken from the era starting in 2036,
+/// as RFC 4330 suggests.
+fn from_ntp(timestamp: u64) -> i128 {
+    let mut secs = (timestamp >> 32) as i64;
+    if secs & 0x8000_0000 == 0 {
+        secs += 1 << 32;
+    }
+    let nanos = ((timestamp & 0xFFFF_FFFF) * 1_000_000_000) >> 32;
+    (secs - NTP_UNIX_OFFSET) as i128 * 1_000_000_000 + nanos as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Instant};
+
+    use super::*;
+
+    /// Run a stand-in server answering the first request with the packets built from it.
+    fn stand_in<F>(answer: F) -> SntpClient
+    where
+        F: FnOnce(&[u8; PACKET_LEN]) -> Vec<[u8; PACKET_LEN]> + Send + 'static,
+    {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut client = SntpClient::new(&server.local_addr().unwrap().to_string()).unwrap();
+        client.set_timeout(Duration::from_millis(300)).unwrap();
+
+        thread::spawn(move || {
+            let mut request = [0; PACKET_LEN];
+            let (_, from) = server.recv_from(&mut request).unwrap();
+            for packet in answer(&request) {
+                // the client may have given up already
+                let _ = server.send_to(&packet, from);
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        client
+    }
+
+    #[test]
+    fn offset_and_delay_from_the_timestamps() {
+        let second = 1u64 << 32;
+        let millis = |ms: u64| (ms << 32) / 1000;
+        let t1 = 3_000_000_000 * second;
+
+        let mut reply = [0; PACKET_LEN];
+        reply[0] = SERVER_HEADER;
+        reply[1] = 2;
+        reply[RECEIVE..RECEIVE + 8].copy_from_slice(&(t1 + 5 * second + millis(10)).to_be_bytes());
+        reply[TRANSMIT..TRANSMIT + 8].copy_from_slice(&(t1 + 5 * second + millis(12)).to_be_bytes());
+
+        let sample = sample(&reply, t1, t1 + millis(20)).unwrap();
+        assert!((sample.offset - 5_001_000_000).abs() <= 1, "{}", sample);
+        assert!(sample.delay.abs_diff(Duration::from_millis(18)) <= Duration::from_nanos(1), "{}", sample);
+        assert_eq!(sample.stratum, 2);
+    }
+
+    #[test]
+    fn query_a_server_ahead() {
+        let offset = Duration::from_millis(3250);
+        let client = stand_in(move |request| {
+            let now = SystemTime::now() + offset;
+            vec![server_reply(request, now, now)]
+        });
+
+        let sample = client.query().unwrap();
+        assert!((sample.offset - 3_250_000_000).abs() < 50_000_000, "{}", sample);
+        assert!(sample.delay < Duration::from_millis(50), "{}", sample);
+        assert_eq!(sample.stratum, 1);
+    }
+
+    #[test]
+    fn replies_to_other_requests_are_skipped() {
+        let client = stand_in(|request| {
+            let now = SystemTime::now();
+            let mut late = server_reply(request, now, now);
+            late[ORIGINATE] ^= 0xFF;
+            vec![late, server_reply(request, now, now)]
+        });
+
+        assert!(client.query().is_ok());
+    }
+
+    #[test]
+    fn stray_packets_do_not_extend_the_timeout() {
+        let client = stand_in(|request| {
+            let now = SystemTime::now();
+            let mut late = server_reply(request, now, now);
+            late[ORIGINATE] ^= 0xFF;
+            // a packet every 20 ms for 2 s
+            vec![late; 100]
+        });
+
+        let start = Instant::now();
+        let error = client.query().unwrap_err();
+        assert!(error.to_string().starts_with("No reply"), "{}", error);
+        assert!(start.elapsed() < Duration::from_millis(600), "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn kiss_of_death_is_an_error() {
+        let client = stand_in(|request| {
+            let now = SystemTime::now();
+            let mut reply = server_reply(request, now, now);
+            reply[1] = 0;
+            reply[12..16].copy_from_slice(b"RATE");
+            vec![reply]
+        });
+
+        let error = client.query().unwrap_err();
+        assert_eq!(error.to_string(), "NTP server refused the query: RATE");
+    }
+
+    #[test]
+    fn silent_server_times_out() {
+        let client = stand_in(|_| Vec::new());
+
+        let start = Instant::now();
+        let error = client.query().unwrap_err();
+        assert!(error.to_string().starts_with("No reply"), "{}", error);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,88 @@
+use std::{env, time::Duration};
+
+use bbb_hal::shutdown::Shutdown;
+use sntp_client::{SntpClient, DEFAULT_SERVER, DEFAULT_STEP_THRESHOLD, DEFAULT_TIMEOUT};
+
+/// Time between two queries by default, the minimum poll interval of ntpd.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(64);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+
+    let args = env::args().collect::<Vec<String>>();
+
+    if args.len() > 1 && (args[1] == "-h" || args[1] == "--help") {
+        let usage_txt = format!(r#"Usage:
+    {} [option] <value>
+
+Options:
+    -h, --help      print help and exit
+    -s              NTP server, host or host:port (default: {})
+    -i              seconds between queries, 0 to set the clock once and exit (default: {})
+    --step          offset in ms above which the clock is stepped instead of slewed (default: {})
+    --timeout       time to wait for the server in ms (default: {})
+    --set           set the clock (on), or only print the offset (off) (default: on)
+"#, args[0], DEFAULT_SERVER, DEFAULT_INTERVAL.as_secs(), DEFAULT_STEP_THRESHOLD.as_millis(), DEFAULT_TIMEOUT.as_millis());
+
+        println!("{}", usage_txt);
+        return Ok(());
+    }
+
+    let mut server = DEFAULT_SERVER.to_string();
+    let mut interval = DEFAULT_INTERVAL;
+    let mut step_threshold = DEFAULT_STEP_THRESHOLD;
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut set_clock = true;
+
+    let mut opts = args[1..].iter();
+    while let Some(opt) = opts.next() {
+        let value = opts.next().ok_or_else(|| format!("Missing value for option: {}", opt))?;
+
+        match opt.as_str() {
+            "-s" => server = value.clone(),
+            "-i" => {
+                interval = Duration::from_secs(value.parse().map_err(|_| format!("Invalid interval: {}", value))?);
+            },
+            "--step" => {
+                step_threshold = Duration::from_millis(value.parse().map_err(|_| format!("Invalid step threshold: {}", value))?);
+            },
+            "--timeout" => {
+                timeout = Duration::from_millis(value.parse().map_err(|_| format!("Invalid timeout: {}", value))?);
+            },
+            "--set" => {
+                set_clock = match value.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("Invalid value for --set: {}", value).into()),
+                };
+            },
+            _ => return Err(format!("Unknown option: {}", opt).into()),
+        }
+    }
+
+    let mut client = SntpClient::new(&server)?;
+    client.set_timeout(timeout)?;
+    let shutdown = Shutdown::install()?;
+
+    loop {
+        let result = client.query().and_then(|sample| {
+            if set_clock {
+                let adjustment = sntp_client::apply(&sample, step_threshold)?;
+                println!("{}: {}, {}", client.server(), sample, adjustment);
+            } else {
+                println!("{}: {}", client.server(), sample);
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) if interval.is_zero() => return Ok(()),
+            Err(e) if interval.is_zero() => return Err(e),
+            Ok(()) => {},
+            Err(e) => eprintln!("{}", e),
+        }
+
+        if !shutdown.sleep(interval) {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,752 @@
+use std::{
+    error::Error,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use bbb_hal::{
+    calendar,
+    gpio::{CdevBackend, GpioBackend},
+    header::HeaderPin,
+    led::UserLed,
+    wiring::Wiring,
+    Value,
+};
+use serde::Deserialize;
+
+use crate::time_zone::{LocalTime, TimeZone};
+
+/// Alarm file used when none is given on the command line.
+pub const DEFAULT_PATH: &str = "/etc/bbb-hal/alarms.toml";
+
+/// Time the button has to be held down to dismiss a ringing alarm.
+pub(crate) const LONG_PRESS: Duration = Duration::from_secs(1);
+
+/// Minutes looked back at most for due alarms, so a clock jumping far ahead
+/// does not scan years of minutes.
+const MAX_CATCH_UP: i64 = 24 * 60;
+
+/// How often an alarm repeats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Repeat {
+    /// Rings once, on the given date or at the next occurrence of the time.
+    #[default]
+    Once,
+    /// Rings every day.
+    Daily,
+    /// Rings from Monday to Friday.
+    Weekdays,
+}
+
+/// A single alarm.
+///
+/// # Fields
+/// * `hour` - The hour, from 0 to 23.
+/// * `minute` - The minute, from 0 to 59.
+/// * `repeat` - How often the alarm rings.
+/// * `date` - The year, month and day a one-shot alarm rings on, `None` for the next occurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alarm {
+    pub hour: u32,
+    pub minute: u32,
+    pub repeat: Repeat,
+    pub date: Option<(i64, u32, u32)>,
+}
+
+impl Alarm {
+    /// Whether the alarm is due at a local time, ignoring the seconds.
+    fn matches(&self, local: &LocalTime) -> bool {
+        if (local.hour, local.minute) != (self.hour, self.minute) {
+            return false;
+        }
+
+        match self.repeat {
+            Repeat::Once => self.date.is_none_or(|date| date == (local.year, local.month, local.day)),
+            Repeat::Daily => true,
+            Repeat::Weekdays => (1..=5).contains(&local.weekday),
+        }
+    }
+}
+
+/// State of the alarms as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmState {
+    /// No alarm ringing.
+    Idle,
+    /// An alarm is ringing since the given Unix time.
+    Ringing { since: i64 },
+    /// A ringing alarm was snoozed and rings again at the given Unix time.
+    Snoozed { until: i64 },
+}
+
+/// Alarm file as written by the user.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlarmFile {
+    #[serde(default)]
+    hardware: HardwareFile,
+    #[serde(default)]
+    alarm: Vec<AlarmEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HardwareFile {
+    buzzer: Option<String>,
+    led: Option<u8>,
+    button: Option<String>,
+    button_active_low: Option<bool>,
+    snooze_minutes: Option<u64>,
+    ring_minutes: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlarmEntry {
+    time: String,
+    #[serde(default)]
+    repeat: Repeat,
+    date: Option<String>,
+}
+
+/// Alarms and the hardware they ring on, as loaded from a TOML file:
+///
+/// ```toml
+/// [hardware]
+/// buzzer = "P8_26"
+/// led = 3
+/// button = "P8_15"
+/// snooze_minutes = 9
+///
+/// [[alarm]]
+/// time = "06:45"
+/// repeat = "weekdays"
+///
+/// [[alarm]]
+/// time = "14:00"
+/// date = "2026-10-24"
+/// ```
+///
+/// # Fields
+/// * `alarms` - The alarms.
+/// * `buzzer` - The header pin driving the buzzer, if any.
+/// * `led` - The user LED flashing with the alarm, if any.
+/// * `button` - The header pin of the snooze/dismiss button, if any.
+/// * `button_active_low` - Whether the button pulls its pin low when pressed.
+/// * `snooze` - The time a snoozed alarm stays quiet.
+/// * `ring_timeout` - The time an alarm rings for when nobody reacts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlarmConfig {
+    pub alarms: Vec<Alarm>,
+    pub buzzer: Option<HeaderPin>,
+    pub led: Option<u8>,
+    pub button: Option<HeaderPin>,
+    pub button_active_low: bool,
+    pub snooze: Duration,
+    pub ring_timeout: Duration,
+}
+
+impl Default for AlarmConfig {
+    fn default() -> Self {
+        Self {
+            alarms: Vec::new(),
+            buzzer: None,
+            led: None,
+            button: None,
+            button_active_low: true,
+            snooze: Duration::from_secs(9 * 60),
+            ring_timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl AlarmConfig {
+    /// Load the alarms from a TOML file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the alarm file.
+    ///
+    /// # Returns
+    /// * `Result<AlarmConfig, Box<dyn Error>>` - The alarms and their hardware.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read alarm file {}: {}", path.display(), e))?;
+
+        Self::from_toml(&text)
+            .map_err(|e| format!("Invalid alarm file {}: {}", path.display(), e).into())
+    }
+
+    /// Load the alarm file given on the command line, falling back to [`DEFAULT_PATH`] if it exists.
+    ///
+    /// # Arguments
+    /// * `path` - The path passed with `--alarms`, if any.
+    ///
+    /// # Returns
+    /// * `Result<Option<AlarmConfig>, Box<dyn Error>>` - The alarms, `None` without an alarm file.
+    pub fn load_or_none(path: Option<&Path>) -> Result<Option<Self>, Box<dyn Error>> {
+        match path {
+            Some(path) => Self::load(path).map(Some),
+            None if Path::new(DEFAULT_PATH).exists() => Self::load(DEFAULT_PATH).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse the alarms from the content of a TOML file.
+    ///
+    /// # Arguments
+    /// * `text` - The TOML document.
+    ///
+    /// # Returns
+    /// * `Result<AlarmConfig, Box<dyn Error>>` - The alarms and their hardware.
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+
+        let file: AlarmFile = toml::from_str(text)?;
+        let mut config = Self::default();
+
+        for entry in &file.alarm {
+            let (hour, minute) = parse_time(&entry.time)?;
+            let date = entry.date.as_deref().map(parse_date).transpose()?;
+            if date.is_some() && entry.repeat != Repeat::Once {
+                return Err(format!("Alarm at {} repeats and cannot have a date", entry.time).into());
+            }
+            config.alarms.push(Alarm { hour, minute, repeat: entry.repeat, date });
+        }
+
+        let hardware = &file.hardware;
+        config.buzzer = hardware.buzzer.as_deref().map(str::parse).transpose()?;
+        config.button = hardware.button.as_deref().map(str::parse).transpose()?;
+        config.led = hardware.led;
+        config.button_active_low = hardware.button_active_low.unwrap_or(config.button_active_low);
+        if let Some(minutes) = hardware.snooze_minutes {
+            config.snooze = Duration::from_secs(minutes * 60);
+        }
+        if let Some(minutes) = hardware.ring_minutes {
+            config.ring_timeout = Duration::from_secs(minutes * 60);
+        }
+
+        let pins = config.buzzer.iter().chain(config.button.iter()).copied().collect::<Vec<_>>();
+        bbb_hal::header::validate(&pins)?;
+
+        Ok(config)
+    }
+}
+
+/// Parse a time of day as `HH:MM`.
+fn parse_time(text: &str) -> Result<(u32, u32), Box<dyn Error>> {
+    let invalid = || format!("Invalid alarm time, expected HH:MM: {}", text);
+
+    let (hour, minute) = text.split_once(':').ok_or_else(invalid)?;
+    let hour = hour.parse().ok().filter(|&h| h < 24).ok_or_else(invalid)?;
+    let minute = minute.parse().ok().filter(|&m| m < 60).ok_or_else(invalid)?;
+
+    Ok((hour, minute))
+}
+
+/// Parse a date as `YYYY-MM-DD`.
+fn parse_date(text: &str) -> Result<(i64, u32, u32), Box<dyn Error>> {
+    let invalid = || format!("Invalid alarm date, expected YYYY-MM-DD: {}", text);
+
+    let mut parts = text.splitn(3, '-');
+    let year = parts.next().and_then(|y| y.parse().ok()).ok_or_else(invalid)?;
+    let month = parts.next().and_then(|m| m.parse().ok()).filter(|m| (1..=12).contains(m)).ok_or_else(invalid)?;
+    let day = parts.next()
+        .and_then(|d| d.parse().ok())
+        .filter(|&d| (1..=calendar::days_in_month(year, month)).contains(&d))
+        .ok_or_else(invalid)?;
+
+    Ok((year, month, day))
+}
+
+/// Decides when alarms ring, from the time shown by the clock.
+///
+/// Every minute between two updates is checked, so alarms are not missed
+/// when the clock is updated rarely or runs fast-forward.
+///
+/// # Fields
+/// * `alarms` - The alarms.
+/// * `done` - Whether every alarm is a one-shot alarm that already rang.
+/// * `snooze` - The time a snoozed alarm stays quiet, in seconds.
+/// * `ring_timeout` - The time an alarm rings for when nobody reacts, in seconds.
+/// * `state` - Whether an alarm is ringing.
+/// * `checked` - The latest minute since the Unix epoch that was checked for alarms.
+#[derive(Debug, Clone)]
+pub struct AlarmClock {
+    alarms: Vec<Alarm>,
+    done: Vec<bool>,
+    snooze: i64,
+    ring_timeout: i64,
+    state: AlarmState,
+    checked: Option<i64>,
+}
+
+impl AlarmClock {
+    /// Create an alarm clock with nothing ringing.
+    ///
+    /// # Arguments
+    /// * `alarms` - The alarms.
+    /// * `snooze` - The time a snoozed alarm stays quiet.
+    /// * `ring_timeout` - The time an alarm rings for when nobody reacts.
+    pub fn new(alarms: Vec<Alarm>, snooze: Duration, ring_timeout: Duration) -> Self {
+        let done = vec![false; alarms.len()];
+        Self {
+            alarms,
+            done,
+            snooze: snooze.as_secs() as i64,
+            ring_timeout: ring_timeout.as_secs() as i64,
+            state: AlarmState::Idle,
+            checked: None,
+        }
+    }
+
+    pub fn state(&self) -> AlarmState {
+        self.state
+    }
+
+    /// Bring the state up to a new time, starting alarms that became due.
+    ///
+    /// # Arguments
+    /// * `unix` - The current time shown by the clock, in seconds since the Unix epoch.
+    /// * `zone` - The time zone the alarm times are in.
+    ///
+    /// # Returns
+    /// * `AlarmState` - The new state.
+    pub fn update(&mut self, unix: i64, zone: &TimeZone) -> AlarmState {
+
+        let minute = unix.div_euclid(60);
+        let checked = match self.checked {
+            // the clock went back by a day or more, e.g. a wrong RTC time being corrected: start afresh
+            Some(checked) if checked - minute >= MAX_CATCH_UP => None,
+            checked => checked,
+        };
+        let first = match checked {
+            Some(checked) => (checked + 1).max(minute - MAX_CATCH_UP + 1),
+            None => minute,
+        };
+
+        // empty when the clock went back by less than a day, e.g. after a time
+        // sync: minutes already checked do not ring twice
+        for m in first..=minute {
+            let local = zone.to_local(m * 60);
+            for (alarm, done) in self.alarms.iter().zip(self.done.iter_mut()) {
+                if *done || !alarm.matches(&local) {
+                    continue;
+                }
+                *done = alarm.repeat == Repeat::Once;
+                self.state = AlarmState::Ringing { since: m * 60 };
+            }
+        }
+        self.checked = Some(checked.map_or(minute, |checked| checked.max(minute)));
+
+        self.state = match self.state {
+            AlarmState::Ringing { since } if unix - since >= self.ring_timeout => AlarmState::Idle,
+            AlarmState::Snoozed { until } if unix >= until => AlarmState::Ringing { since: until },
+            state => state,
+        };
+
+        self.state
+    }
+
+    /// Silence a ringing alarm for the snooze time.
+    ///
+    /// # Arguments
+    /// * `unix` - The current time shown by the clock, in seconds since the Unix epoch.
+    pub fn snooze(&mut self, unix: i64) {
+        if let AlarmState::Ringing { .. } = self.state {
+            self.state = AlarmState::Snoozed { until: unix + self.snooze };
+        }
+    }
+
+    /// Stop a ringing or snoozed alarm until its next occurrence.
+    pub fn dismiss(&mut self) {
+        self.state = AlarmState::Idle;
+    }
+}
+
+/// What the user did with the button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonPress {
+    /// Pressed and released within [`LONG_PRESS`].
+    Short,
+    /// Held down for [`LONG_PRESS`].
+    Long,
+}
+
+/// Buzzer, user LED and button of the alarms.
+///
+/// # Fields
+/// * `backend` - The GPIO backend the buzzer and button are requested on.
+/// * `buzzer` - The GPIO number of the buzzer, if any.
+/// * `led` - The user LED, if any.
+/// * `button` - The GPIO number of the button, if any.
+/// * `pressed_since` - When the button was pressed, while it is held down.
+/// * `long_press_sent` - Whether the current press was already reported as long.
+pub struct AlarmOutputs {
+    backend: Box<dyn GpioBackend>,
+    buzzer: Option<u32>,
+    led: Option<UserLed>,
+    button: Option<u32>,
+    pressed_since: Option<Instant>,
+    long_press_sent: bool,
+}
+
+impl AlarmOutputs {
+    /// Request the buzzer and button lines and take the user LED.
+    ///
+    /// # Arguments
+    /// * `config` - The alarm hardware.
+    /// * `wiring` - The display wiring, whose pins the buzzer and button must not use.
+    ///
+    /// # Returns
+    /// * `Result<AlarmOutputs, Box<dyn Error>>` - The outputs, silent.
+    pub fn new(config: &AlarmConfig, wiring: &Wiring) -> Result<Self, Box<dyn Error>> {
+        let led = config.led.map(UserLed::open).transpose()?;
+        Self::with_backend(Box::new(CdevBackend::new()), config, wiring, led)
+    }
+
+    /// Use the buzzer and button through another backend, e.g. a mock in tests.
+    ///
+    /// # Arguments
+    /// * `backend` - The GPIO backend.
+    /// * `config` - The alarm hardware.
+    /// * `wiring` - The display wiring, whose pins the buzzer and button must not use.
+    /// * `led` - The user LED, if any.
+    ///
+    /// # Returns
+    /// * `Result<AlarmOutputs, Box<dyn Error>>` - The outputs, silent, or an error if a pin
+    ///   is taken by the display or cannot be requested.
+    pub fn with_backend(mut backend: Box<dyn GpioBackend>, config: &AlarmConfig, wiring: &Wiring, led: Option<UserLed>) -> Result<Self, Box<dyn Error>> {
+
+        let pins = wiring.segment_pins()
+            .into_iter()
+            .chain(wiring.digit_pins())
+            .chain(config.buzzer)
+            .chain(config.button)
+            .collect::<Vec<_>>();
+        bbb_hal::header::validate(&pins)
+            .map_err(|e| format!("Alarm hardware clashes with the display wiring: {}", e))?;
+
+        let buzzer = config.buzzer.map(HeaderPin::gpio);
+        if let Some(pin) = buzzer {
+            backend.request_outputs(&[pin], Value::Inactive, false)?;
+        }
+
+        let button = config.button.map(HeaderPin::gpio);
+        if let Some(pin) = button {
+            backend.request_inputs(&[pin], config.button_active_low)?;
+        }
+
+        Ok(Self { backend, buzzer, led, button, pressed_since: None, long_press_sent: false })
+    }
+
+    pub fn has_button(&self) -> bool {
+        self.button.is_some()
+    }
+
+    /// Turn the buzzer and LED on or off.
+    ///
+    /// # Arguments
+    /// * `on` - Whether they are on.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if an output cannot be written.
+    pub fn set(&mut self, on: bool) -> Result<(), Box<dyn Error>> {
+        if let Some(pin) = self.buzzer {
+            self.backend.set_line(pin, if on { Value::Active } else { Value::Inactive })?;
+        }
+        if let Some(led) = &mut self.led {
+            led.set(on)?;
+        }
+        Ok(())
+    }
+
+    /// Sample the button.
+    ///
+    /// # Returns
+    /// * `Result<Option<ButtonPress>, Box<dyn Error>>` - A press completed since the last call, if any.
+    pub fn poll_button(&mut self) -> Result<Option<ButtonPress>, Box<dyn Error>> {
+        self.poll_button_at(Instant::now())
+    }
+
+    /// Sample the button at a given time, see [`AlarmOutputs::poll_button`].
+    fn poll_button_at(&mut self, now: Instant) -> Result<Option<ButtonPress>, Box<dyn Error>> {
+
+        let Some(pin) = self.button else {
+            return Ok(None);
+        };
+        let pressed = self.backend.get_line(pin)? == Value::Active;
+
+        let press = match (pressed, self.pressed_since) {
+            (true, None) => {
+                self.pressed_since = Some(now);
+                self.long_press_sent = false;
+                None
+            },
+            (true, Some(since)) if !self.long_press_sent && now.duration_since(since) >= LONG_PRESS => {
+                self.long_press_sent = true;
+                Some(ButtonPress::Long)
+            },
+            (true, Some(_)) => None,
+            (false, Some(_)) => {
+                self.pressed_since = None;
+                (!self.long_press_sent).then_some(ButtonPress::Short)
+            },
+            (false, None) => None,
+        };
+
+        Ok(press)
+    }
+}
+
+/// Drop implementation to silence the buzzer.
+impl Drop for AlarmOutputs {
+    fn drop(&mut self) {
+        let _ = self.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bbb_hal::{gpio::mock::MockBackend, multiplex::Digits, seven_segment::Segment};
+
+    use super::*;
+
+    /// Saturday 2026-10-17 00:00:00 UTC.
+    const SATURDAY: i64 = 1_792_195_200;
+    const DAY: i64 = 24 * 3600;
+
+    fn alarm(time: &str, repeat: Repeat, date: Option<(i64, u32, u32)>) -> Alarm {
+        let (hour, minute) = parse_time(time).unwrap();
+        Alarm { hour, minute, repeat, date }
+    }
+
+    fn clock(alarms: Vec<Alarm>) -> AlarmClock {
+        AlarmClock::new(alarms, Duration::from_secs(9 * 60), Duration::from_secs(10 * 60))
+    }
+
+    /// The times an alarm starts ringing, updating every 30 s and dismissing at once.
+    fn rings(clock: &mut AlarmClock, from: i64, to: i64) -> Vec<i64> {
+        let zone = TimeZone::utc();
+        let mut started = Vec::new();
+        for unix in (from..to).step_by(30) {
+            if let AlarmState::Ringing { since } = clock.update(unix, &zone) {
+                started.push(since);
+                clock.dismiss();
+            }
+        }
+        started
+    }
+
+    #[test]
+    fn from_toml_rejects_days_past_the_end_of_the_month() {
+        let file = |date: &str| format!("[[alarm]]\ntime = \"07:00\"\ndate = \"{}\"\n", date);
+
+        for date in ["2026-02-29", "2026-02-31", "2026-04-31", "2026-06-31", "2026-12-32", "2026-01-00"] {
+            let error = AlarmConfig::from_toml(&file(date)).unwrap_err();
+            assert_eq!(error.to_string(), format!("Invalid alarm date, expected YYYY-MM-DD: {}", date));
+        }
+
+        let config = AlarmConfig::from_toml(&file("2028-02-29")).unwrap();
+        assert_eq!(config.alarms[0].date, Some((2028, 2, 29)));
+        assert!(AlarmConfig::from_toml(&file("2026-04-30")).is_ok());
+    }
+
+    #[test]
+    fn outputs_reject_pins_of_the_display() {
+        let mut wiring = Wiring::default();
+        wiring.set_digit(Digits::D_4, HeaderPin::P8_26);
+
+        for (buzzer, button) in [(Some(HeaderPin::P8_26), None), (None, Some(wiring.segment(Segment::A)))] {
+            let config = AlarmConfig { buzzer, button, ..AlarmConfig::default() };
+            let Err(error) = AlarmOutputs::with_backend(Box::new(MockBackend::new()), &config, &wiring, None) else {
+                panic!("{:?} and {:?} are display pins", buzzer, button);
+            };
+            assert!(error.to_string().starts_with("Alarm hardware clashes with the display wiring: Pin"), "{}", error);
+        }
+
+        let config = AlarmConfig { buzzer: Some(HeaderPin::P8_26), ..AlarmConfig::default() };
+        assert!(AlarmOutputs::with_backend(Box::new(MockBackend::new()), &config, &Wiring::default(), None).is_ok());
+    }
+
+    const BUZZER: HeaderPin = HeaderPin::P8_26;
+    const BUTTON: HeaderPin = HeaderPin::P8_15;
+
+    fn outputs(led: Option<UserLed>) -> (AlarmOutputs, MockBackend) {
+        let mock = MockBackend::new();
+        let config = AlarmConfig { buzzer: Some(BUZZER), button: Some(BUTTON), ..AlarmConfig::default() };
+        let outputs = AlarmOutputs::with_backend(Box::new(mock.clone()), &config, &Wiring::default(), led).unwrap();
+        (outputs, mock)
+    }
+
+    #[test]
+    fn short_press_is_reported_on_release() {
+        let (mut outputs, mock) = outputs(None);
+        let start = Instant::now();
+        assert!(outputs.has_button());
+        assert_eq!(mock.is_active_low(BUTTON.gpio()), Some(true));
+
+        assert_eq!(outputs.poll_button_at(start).unwrap(), None);
+        mock.set_level(BUTTON.gpio(), Value::Active);
+        assert_eq!(outputs.poll_button_at(start).unwrap(), None);
+        assert_eq!(outputs.poll_button_at(start + LONG_PRESS / 2).unwrap(), None);
+        mock.set_level(BUTTON.gpio(), Value::Inactive);
+        assert_eq!(outputs.poll_button_at(start + LONG_PRESS - Duration::from_millis(1)).unwrap(), Some(ButtonPress::Short));
+        assert_eq!(outputs.poll_button_at(start + LONG_PRESS).unwrap(), None);
+    }
+
+    #[test]
+    fn long_press_is_reported_once_while_held() {
+        let (mut outputs, mock) = outputs(None);
+        let start = Instant::now();
+
+        mock.set_level(BUTTON.gpio(), Value::Active);
+        assert_eq!(outputs.poll_button_at(start).unwrap(), None);
+        assert_eq!(outputs.poll_button_at(start + LONG_PRESS - Duration::from_millis(1)).unwrap(), None);
+        assert_eq!(outputs.poll_button_at(start + LONG_PRESS).unwrap(), Some(ButtonPress::Long));
+        assert_eq!(outputs.poll_button_at(start + 3 * LONG_PRESS).unwrap(), None);
+
+        // no short press on release, and the next press starts afresh
+        mock.set_level(BUTTON.gpio(), Value::Inactive);
+        assert_eq!(outputs.poll_button_at(start + 4 * LONG_PRESS).unwrap(), None);
+        mock.set_level(BUTTON.gpio(), Value::Active);
+        assert_eq!(outputs.poll_button_at(start + 5 * LONG_PRESS).unwrap(), None);
+        mock.set_level(BUTTON.gpio(), Value::Inactive);
+        assert_eq!(outputs.poll_button_at(start + 5 * LONG_PRESS).unwrap(), Some(ButtonPress::Short));
+    }
+
+    #[test]
+    fn no_button_never_reports_a_press() {
+        let mock = MockBackend::new();
+        let config = AlarmConfig { buzzer: Some(BUZZER), ..AlarmConfig::default() };
+        let mut outputs = AlarmOutputs::with_backend(Box::new(mock.clone()), &config, &Wiring::default(), None).unwrap();
+
+        assert!(!outputs.has_button());
+        mock.set_level(BUTTON.gpio(), Value::Active);
+        assert_eq!(outputs.poll_button().unwrap(), None);
+    }
+
+    #[test]
+    fn set_drives_the_buzzer_and_led() {
+        let root = std::env::temp_dir().join(format!("bbb-alarm-led-{}", std::process::id()));
+        let led_dir = root.join("beaglebone:green:usr3");
+        fs::create_dir_all(&led_dir).unwrap();
+        fs::write(led_dir.join("trigger"), "none [heartbeat] timer").unwrap();
+        let brightness = || fs::read_to_string(led_dir.join("brightness")).unwrap();
+
+        let (mut outputs, mock) = outputs(Some(UserLed::open_at(&root, 3).unwrap()));
+        let buzzer = BUZZER.gpio();
+        assert_eq!((mock.is_active_low(buzzer), mock.is_high(buzzer)), (Some(false), Some(false)));
+
+        outputs.set(true).unwrap();
+        assert_eq!(mock.is_high(buzzer), Some(true));
+        assert_eq!(brightness(), "1");
+
+        outputs.set(false).unwrap();
+        assert_eq!(mock.is_high(buzzer), Some(false));
+        assert_eq!(brightness(), "0");
+
+        // silenced and the LED given back on drop
+        outputs.set(true).unwrap();
+        drop(outputs);
+        assert_eq!(mock.writes().last(), Some(&(buzzer, Value::Inactive)));
+        assert_eq!(brightness(), "0");
+        assert_eq!(fs::read_to_string(led_dir.join("trigger")).unwrap(), "heartbeat");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn once_rings_at_the_next_occurrence_only() {
+        let mut clock = clock(vec![alarm("06:45", Repeat::Once, None)]);
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        assert_eq!(rings(&mut clock, SATURDAY, SATURDAY + 3 * DAY), [at]);
+    }
+
+    #[test]
+    fn once_on_a_date() {
+        let mut clock = clock(vec![alarm("14:00", Repeat::Once, Some((2026, 10, 18)))]);
+        let at = SATURDAY + DAY + 14 * 3600;
+        assert_eq!(rings(&mut clock, SATURDAY, SATURDAY + 3 * DAY), [at]);
+    }
+
+    #[test]
+    fn daily_rings_every_day() {
+        let mut clock = clock(vec![alarm("06:45", Repeat::Daily, None)]);
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        assert_eq!(rings(&mut clock, SATURDAY, SATURDAY + 3 * DAY), [at, at + DAY, at + 2 * DAY]);
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        let mut clock = clock(vec![alarm("06:45", Repeat::Weekdays, None)]);
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        // Saturday to Tuesday
+        assert_eq!(rings(&mut clock, SATURDAY, SATURDAY + 4 * DAY), [at + 2 * DAY, at + 3 * DAY]);
+    }
+
+    #[test]
+    fn minutes_skipped_by_a_jump_are_caught_up() {
+        let zone = TimeZone::utc();
+        let mut clock = clock(vec![alarm("06:45", Repeat::Daily, None)]);
+        clock.update(SATURDAY + 6 * 3600, &zone);
+        assert_eq!(clock.update(SATURDAY + 6 * 3600 + 50 * 60, &zone), AlarmState::Ringing { since: SATURDAY + 6 * 3600 + 45 * 60 });
+    }
+
+    #[test]
+    fn going_back_does_not_ring_twice() {
+        let zone = TimeZone::utc();
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        let mut clock = clock(vec![alarm("06:45", Repeat::Daily, None)]);
+        assert_eq!(rings(&mut clock, at - 60, at + 3600), [at]);
+
+        // back by an hour: 06:45 was already checked
+        assert_eq!(rings(&mut clock, at - 60, at + 3600), []);
+
+        // back by two days: checked afresh
+        clock.update(at - 2 * DAY - 60, &zone);
+        assert_eq!(clock.update(at - 2 * DAY, &zone), AlarmState::Ringing { since: at - 2 * DAY });
+    }
+
+    #[test]
+    fn snooze_rings_again_after_the_snooze_time() {
+        let zone = TimeZone::utc();
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        let mut clock = clock(vec![alarm("06:45", Repeat::Once, None)]);
+        assert_eq!(clock.update(at + 10, &zone), AlarmState::Ringing { since: at });
+
+        clock.snooze(at + 20);
+        let until = at + 20 + 9 * 60;
+        assert_eq!(clock.state(), AlarmState::Snoozed { until });
+        assert_eq!(clock.update(until - 1, &zone), AlarmState::Snoozed { until });
+        assert_eq!(clock.update(until, &zone), AlarmState::Ringing { since: until });
+
+        // snoozing only applies to a ringing alarm
+        clock.dismiss();
+        clock.snooze(until + 1);
+        assert_eq!(clock.state(), AlarmState::Idle);
+    }
+
+    #[test]
+    fn dismiss_stops_a_snoozed_alarm() {
+        let zone = TimeZone::utc();
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        let mut clock = clock(vec![alarm("06:45", Repeat::Once, None)]);
+        clock.update(at, &zone);
+        clock.snooze(at);
+        clock.dismiss();
+        assert_eq!(clock.update(at + DAY, &zone), AlarmState::Idle);
+    }
+
+    #[test]
+    fn ringing_times_out() {
+        let zone = TimeZone::utc();
+        let at = SATURDAY + 6 * 3600 + 45 * 60;
+        let mut clock = clock(vec![alarm("06:45", Repeat::Once, None)]);
+        assert_eq!(clock.update(at, &zone), AlarmState::Ringing { since: at });
+        assert_eq!(clock.update(at + 10 * 60 - 1, &zone), AlarmState::Ringing { since: at });
+        assert_eq!(clock.update(at + 10 * 60, &zone), AlarmState::Idle);
+    }
+}
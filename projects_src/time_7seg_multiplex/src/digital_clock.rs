@@ -1,10 +1,48 @@
 use std::{
     error::Error,
-    thread,
+    ops::Range,
     time::{Duration, SystemTime},
 };
 
-use crate::seven_segment::{Digits, SevenSegmentDisplay, Segment};
+use bbb_hal::{
+    font::Glyph,
+    multiplex::{Frame, FrameBuffer, MultiplexedDisplay, ScanTiming, DIGITS, SEPARATOR_DIGIT},
+    realtime::{JitterStats, RealtimeConfig},
+    refresh::RefreshThread,
+    rtc::Ds3231,
+    shutdown::Shutdown,
+    system_clock::{self, SyncStatus},
+    wiring::Wiring,
+};
+
+use crate::{
+    alarm::{AlarmClock, AlarmOutputs, AlarmState, ButtonPress},
+    time_source::{SystemClock, TimeSource},
+    time_zone::TimeZone,
+};
+
+/// Time between two updates of the frame buffer.
+const UPDATE_PERIOD: Duration = Duration::from_millis(200);
+
+/// Offset of the HH:MM:SS window over the six digits for every second of a
+/// 6-second cycle: hours and minutes for three seconds, then a scroll to minutes and seconds.
+const SCROLL_CYCLE: [usize; 6] = [0, 0, 0, 1, 2, 2];
+
+/// Time between two samples of the alarm button.
+const BUTTON_POLL_PERIOD: Duration = Duration::from_millis(50);
+
+/// Index of the digit whose decimal point blinks while the system clock is not synchronized.
+const UNSYNC_DIGIT: usize = 0;
+
+/// Index of the digit whose decimal point marks PM times.
+const PM_DIGIT: usize = 3;
+
+/// Seconds of every minute the AM/PM text is flashed during.
+const AM_PM_FLASH: Range<u8> = 30..32;
+
+/// Left and right halves of an `M` drawn over two digits.
+const M_LEFT: Glyph = Glyph::from_bits(0x33);
+const M_RIGHT: Glyph = Glyph::from_bits(0x27);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ClockFormat {
@@ -12,68 +50,891 @@ pub enum ClockFormat {
     TwentyFourHour
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisplayMode {
+    /// Hours and minutes.
+    HourMinute,
+    /// Minutes and seconds.
+    MinuteSecond,
+    /// Hours, minutes and seconds, scrolling over the four digits.
+    HourMinuteSecond,
+}
+
+/// How PM times are told apart from AM times in 12-hour format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AmPmIndicator {
+    /// No indication.
+    Off,
+    /// The decimal point of the last digit is lit in the afternoon, in
+    /// hours and minutes mode only: the scrolling HH:MM:SS has its decimal
+    /// points taken by the separators.
+    Dot,
+    /// `AM` or `PM` is shown for two seconds every minute.
+    Flash,
+}
+
+/// The time as shown by the clock.
+///
+/// # Fields
+/// * `hours` - The hours, from 1 to 12 in 12-hour format.
+/// * `minutes` - The minutes.
+/// * `seconds` - The seconds.
+/// * `pm` - Whether the time is in the afternoon, `None` in 24-hour format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockTime {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub pm: Option<bool>,
+}
+
+/// Behaviour of the separator decimal points, synchronized to the second boundaries of the time source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Separator {
+    /// Always lit.
+    On,
+    /// Never lit.
+    Off,
+    /// Lit during the first half of every second.
+    Blink,
+    /// Lit twice during the first half of every second.
+    DoubleBlink,
+}
+
+impl Separator {
+    /// Times in ms after the second boundary the separator turns on and off, in order.
+    fn edges(self) -> &'static [u32] {
+        match self {
+            Separator::On | Separator::Off => &[],
+            Separator::Blink => &[0, 500],
+            Separator::DoubleBlink => &[0, 150, 300, 450],
+        }
+    }
+
+    /// Whether the separator is lit at some ms after the second boundary.
+    fn is_lit(self, millis: u32) -> bool {
+        match self {
+            Separator::On => true,
+            Separator::Off => false,
+            _ => self.edges().iter().filter(|&&edge| edge <= millis).count() % 2 == 1,
+        }
+    }
+
+    /// Time from some ms after the second boundary to the next change of the separator.
+    fn until_next_edge(self, millis: u32) -> Option<Duration> {
+        let edges = self.edges();
+        if edges.is_empty() {
+            return None;
+        }
+
+        // the first edge of the next second
+        let next = edges.iter().copied().find(|&edge| edge > millis).unwrap_or(1000);
+        Some(Duration::from_millis((next - millis) as u64))
+    }
+}
+
+/// Order of the day and month when the date is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DateOrder {
+    /// `DD.MM`, as in most of Europe.
+    DayMonth,
+    /// `MM.DD`, as in the US.
+    MonthDay,
+}
+
+/// What the display shows at a point of the cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum View {
+    Time,
+    Date,
+    Year,
+}
+
+/// Repeating schedule of the time, date and year views, aligned to the Unix epoch
+/// so it does not depend on when the clock was started.
+///
+/// # Fields
+/// * `time` - How long the time is shown.
+/// * `date` - How long the date is shown.
+/// * `year` - How long the year is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CycleSchedule {
+    pub time: Duration,
+    pub date: Duration,
+    pub year: Duration,
+}
+
+impl Default for CycleSchedule {
+    /// The time only.
+    fn default() -> Self {
+        Self { time: Duration::from_secs(1), date: Duration::ZERO, year: Duration::ZERO }
+    }
+}
+
+impl CycleSchedule {
+    /// Parse a schedule given as seconds of time, date and year, e.g. `20,3,2`.
+    ///
+    /// # Arguments
+    /// * `spec` - The three durations in seconds, separated by commas.
+    ///
+    /// # Returns
+    /// * `Result<CycleSchedule, Box<dyn Error>>` - The schedule, or an error if the text is invalid or all durations are zero.
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let invalid = || format!("Invalid cycle, expected <time>,<date>,<year> in seconds: {}", spec);
+
+        let secs = spec
+            .split(',')
+            .map(|part| part.trim().parse().map(Duration::from_secs).map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [time, date, year] = secs[..] else {
+            return Err(invalid().into());
+        };
+
+        let schedule = Self { time, date, year };
+        if schedule.period().is_zero() {
+            return Err(invalid().into());
+        }
+
+        Ok(schedule)
+    }
+
+    /// Total length of one cycle.
+    pub fn period(&self) -> Duration {
+        self.time + self.date + self.year
+    }
+
+    /// The view shown at a time.
+    ///
+    /// # Arguments
+    /// * `since_epoch` - The time shown by the clock, since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `View` - The view of the schedule at that time.
+    pub fn view_at(&self, since_epoch: Duration) -> View {
+        let period = self.period().as_millis();
+        if period == 0 {
+            return View::Time;
+        }
+
+        let offset = since_epoch.as_millis() % period;
+        if offset < self.time.as_millis() {
+            View::Time
+        } else if offset < (self.time + self.date).as_millis() {
+            View::Date
+        } else {
+            View::Year
+        }
+    }
+}
+
 pub struct DigitalClock {
-    display: SevenSegmentDisplay,
+    refresh: RefreshThread,
+    buffer: FrameBuffer,
     format: ClockFormat,
+    mode: DisplayMode,
+    separator: Separator,
+    am_pm: AmPmIndicator,
+    cycle: CycleSchedule,
+    date_order: DateOrder,
+    zone: TimeZone,
+    source: Box<dyn TimeSource>,
+    alarm: Option<(AlarmClock, AlarmOutputs)>,
+    sync_check: bool,
+    sync: SyncStatus,
+    rtc: Option<Ds3231>,
+    shutdown: Shutdown,
 }
 
 impl DigitalClock {
-    pub fn new(format: ClockFormat) -> Result<Self, Box<dyn Error>> {
-        let display = SevenSegmentDisplay::new()?;
-        Ok(Self {display, format})
+    pub fn new(format: ClockFormat, zone: TimeZone, wiring: Wiring, timing: ScanTiming, realtime: Option<RealtimeConfig>, shutdown: Shutdown) -> Result<Self, Box<dyn Error>> {
+        let display = MultiplexedDisplay::with_wiring(wiring)?;
+        Self::with_display(display, format, zone, timing, realtime, shutdown)
     }
 
-    pub fn get_current_time(&self) -> (u8, u8){
-        
-        let now = SystemTime::now()
+    pub fn with_display(display: MultiplexedDisplay, format: ClockFormat, zone: TimeZone, timing: ScanTiming, realtime: Option<RealtimeConfig>, shutdown: Shutdown) -> Result<Self, Box<dyn Error>> {
+        let refresh = match realtime {
+            Some(config) => RefreshThread::spawn_realtime(display, timing, config)?,
+            None => RefreshThread::spawn(display, timing)?,
+        };
+        let buffer = refresh.buffer();
+        Ok(Self {
+            refresh,
+            buffer,
+            format,
+            mode: DisplayMode::HourMinute,
+            separator: Separator::Blink,
+            am_pm: AmPmIndicator::Dot,
+            cycle: CycleSchedule::default(),
+            date_order: DateOrder::DayMonth,
+            zone,
+            source: Box::new(SystemClock),
+            alarm: None,
+            sync_check: true,
+            sync: SyncStatus::Synchronized,
+            rtc: None,
+            shutdown,
+        })
+    }
+
+    pub fn set_mode(&mut self, mode: DisplayMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_separator(&mut self, separator: Separator) {
+        self.separator = separator;
+    }
+
+    pub fn set_am_pm(&mut self, am_pm: AmPmIndicator) {
+        self.am_pm = am_pm;
+    }
+
+    pub fn set_cycle(&mut self, cycle: CycleSchedule) {
+        self.cycle = cycle;
+    }
+
+    pub fn set_date_order(&mut self, order: DateOrder) {
+        self.date_order = order;
+    }
+
+    /// Show the time from another source; the status of the system clock is no longer checked.
+    pub fn set_time_source(&mut self, source: Box<dyn TimeSource>) {
+        self.source = source;
+        self.sync_check = false;
+    }
+
+    /// Whether `----` is shown while the system clock was never set, and the
+    /// first decimal point blinks while it is not synchronized.
+    pub fn set_sync_check(&mut self, enabled: bool) {
+        self.sync_check = enabled;
+    }
+
+    /// Keep the system clock and a battery-backed RTC in step: the system clock
+    /// is set from the RTC now if it was never set, and the RTC from the system
+    /// clock whenever it becomes synchronized. RTC errors are only logged.
+    pub fn set_rtc(&mut self, mut rtc: Ds3231) -> Result<(), Box<dyn Error>> {
+        self.sync = system_clock::status()?;
+        match self.sync {
+            // the clock still runs without the RTC time, showing ----
+            SyncStatus::Unset => match rtc.read_time().and_then(system_clock::set_time) {
+                Ok(()) => println!("System clock set from the RTC"),
+                Err(e) => eprintln!("Cannot set the system clock from the RTC: {}", e),
+            },
+            SyncStatus::Synchronized => if let Err(e) = rtc.write_time(SystemTime::now()) {
+                eprintln!("Cannot save the system clock to the RTC: {}", e);
+            },
+            SyncStatus::Unsynchronized => {},
+        }
+        self.rtc = Some(rtc);
+        Ok(())
+    }
+
+    pub fn set_alarms(&mut self, alarms: AlarmClock, outputs: AlarmOutputs) {
+        self.alarm = Some((alarms, outputs));
+    }
+
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn Error>> {
+        self.refresh.brightness().set(level)
+    }
+
+    pub fn stop(self) -> Result<Option<JitterStats>, Box<dyn Error>> {
+        let jitter = self.refresh.jitter();
+        self.refresh.stop()?;
+        Ok(jitter)
+    }
+
+    fn now(&self) -> Duration {
+        self.source
+            .now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
-            .as_secs();
+    }
 
-        let seconds = now % (60 * 60 * 24);
-        let hours = (seconds / (60 * 60)) as u8;
-        let minutes = ((seconds % (60 * 60)) / 60) as u8;
+    pub fn get_current_time(&self) -> ClockTime {
 
-        let display_hours = match self.format {
+        let local = self.zone.to_local(self.now().as_secs() as i64);
+        let (hours, pm) = match self.format {
             ClockFormat::TwelveHour => {
-                if hours == 0 { 12 }
-                else if hours > 12 { hours - 12 } 
-                else { hours }
+                let (hours, pm) = twelve_hour(local.hour as u8);
+                (hours, Some(pm))
             },
-            ClockFormat::TwentyFourHour => hours,
+            ClockFormat::TwentyFourHour => (local.hour as u8, None),
         };
 
-        (display_hours, minutes)
+        ClockTime { hours, minutes: local.minute as u8, seconds: local.second as u8, pm }
+    }
+
+    /// Show the date or the year, with the decimal point separating day and month.
+    pub fn display_date(&mut self, view: View) -> Result<(), Box<dyn Error>> {
+        let local = self.zone.to_local(self.now().as_secs() as i64);
+
+        let text = match (view, self.date_order) {
+            (View::Year, _) => format!("{:>4}", local.year),
+            (_, DateOrder::DayMonth) => format!("{:02}.{:02}", local.day, local.month),
+            (_, DateOrder::MonthDay) => format!("{:02}.{:02}", local.month, local.day),
+        };
+        self.buffer.set(bbb_hal::text::string(&text)?);
+
+        Ok(())
+    }
+
+    /// Refresh the status of the system clock, saving it to the RTC when it becomes synchronized.
+    ///
+    /// Errors are only logged, the clock keeps showing the time with the last known status.
+    fn update_sync(&mut self) {
+        if !self.sync_check && self.rtc.is_none() {
+            return;
+        }
+
+        let status = match system_clock::status() {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            },
+        };
+        if status == SyncStatus::Synchronized && self.sync != SyncStatus::Synchronized {
+            if let Some(Err(e)) = self.rtc.as_mut().map(|rtc| rtc.write_time(SystemTime::now())) {
+                eprintln!("Cannot save the system clock to the RTC: {}", e);
+            }
+        }
+        self.sync = status;
     }
 
     pub fn display_time(&mut self) -> Result<(), Box<dyn Error>> {
-        let (hours, minutes) = self.get_current_time();
+        self.update_sync();
+        self.show_time()
+    }
 
-        let digits = [
-            hours / 10,
-            hours % 10,
-            minutes / 10,
-            minutes % 10
-        ];
+    /// Show the time, or the date when the cycle says so, with the last known status of the system clock.
+    fn show_time(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.sync_check && self.sync == SyncStatus::Unset {
+            self.buffer.set([Glyph::MINUS; DIGITS]);
+            return Ok(());
+        }
+
+        let view = self.cycle.view_at(self.now());
+        if view != View::Time {
+            return self.display_date(view);
+        }
+
+        let time = self.get_current_time();
+        let lit = self.separator.is_lit(self.now().subsec_millis());
+
+        // leading zero of the hours blanked in 12-hour format
+        let hour_digits = match time.pm {
+            Some(_) => format!("{:>2}", time.hours),
+            None => format!("{:02}", time.hours),
+        };
 
-        let display_digits = [Digits::D_1, Digits::D_2, Digits::D_3, Digits::D_4];
+        let mut frame = match self.mode {
+            DisplayMode::HourMinute => {
+                let mut frame = bbb_hal::text::string(&format!("{}{:02}", hour_digits, time.minutes))?;
+                frame[SEPARATOR_DIGIT] = frame[SEPARATOR_DIGIT].with_dp(lit);
+                frame
+            },
+            DisplayMode::MinuteSecond => {
+                let mut frame = bbb_hal::text::string(&format!("{:02}{:02}", time.minutes, time.seconds))?;
+                frame[SEPARATOR_DIGIT] = frame[SEPARATOR_DIGIT].with_dp(lit);
+                frame
+            },
+            DisplayMode::HourMinuteSecond => {
+                let strip = format!("{}{:02}{:02}", hour_digits, time.minutes, time.seconds)
+                    .chars()
+                    .map(|c| Glyph::from_char(c).unwrap_or(Glyph::BLANK))
+                    .enumerate()
+                    .map(|(i, glyph)| glyph.with_dp(lit && (i == SEPARATOR_DIGIT || i == SEPARATOR_DIGIT + 2)))
+                    .collect::<Vec<_>>();
 
-        for (i, &dig_pos) in display_digits.iter().enumerate() {
-            if dig_pos == Digits::D_3 {
-                self.display.set_segment(Segment::DP, gpiocdev::line::Value::Active)?;
-                thread::sleep(Duration::from_millis(1));
+                let offset = SCROLL_CYCLE[time.seconds as usize % SCROLL_CYCLE.len()];
+                let mut frame: Frame = [Glyph::BLANK; DIGITS];
+                frame.copy_from_slice(&strip[offset..offset + DIGITS]);
+                frame
+            },
+        };
+
+        // minutes and seconds do not tell AM from PM
+        if let (Some(pm), false) = (time.pm, self.mode == DisplayMode::MinuteSecond) {
+            match self.am_pm {
+                AmPmIndicator::Off => {},
+                AmPmIndicator::Dot if self.mode == DisplayMode::HourMinute => frame[PM_DIGIT] = frame[PM_DIGIT].with_dp(pm),
+                AmPmIndicator::Dot => {},
+                AmPmIndicator::Flash if AM_PM_FLASH.contains(&time.seconds) => {
+                    let letter = Glyph::from_char(if pm { 'P' } else { 'A' }).unwrap_or(Glyph::BLANK);
+                    frame = [letter, M_LEFT, M_RIGHT, Glyph::BLANK];
+                },
+                AmPmIndicator::Flash => {},
             }
-            self.display.enable_digit(dig_pos)?;
-            self.display.set_digit(digits[i])?;
-            thread::sleep(Duration::from_millis(3));
+        }
+
+        // lit in the second half of every second, opposite to the separator
+        if self.sync_check && self.sync == SyncStatus::Unsynchronized {
+            let millis = self.now().subsec_millis();
+            frame[UNSYNC_DIGIT] = frame[UNSYNC_DIGIT].with_dp(millis >= 500);
+        }
+
+        self.buffer.set(frame);
+
+        Ok(())
+    }
+
+    /// Ring due alarms and handle the button, flashing the display and
+    /// beeping in sync with the seconds while an alarm rings.
+    pub fn update_alarms(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = self.now();
+        let unix = now.as_secs() as i64;
+        let Some((alarms, outputs)) = &mut self.alarm else {
+            return Ok(());
+        };
+
+        let state = alarms.update(unix, &self.zone);
+        match (outputs.poll_button()?, state) {
+            (Some(ButtonPress::Short), AlarmState::Ringing { .. }) => alarms.snooze(unix),
+            (Some(ButtonPress::Long), AlarmState::Ringing { .. } | AlarmState::Snoozed { .. }) => alarms.dismiss(),
+            _ => {},
+        }
+
+        let ringing = matches!(alarms.state(), AlarmState::Ringing { .. });
+        let on = ringing && now.subsec_millis() < 500;
+        outputs.set(on)?;
+        if ringing && !on {
+            self.buffer.set([Glyph::BLANK; DIGITS]);
         }
 
         Ok(())
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        loop {
+        while !self.shutdown.is_requested() {
+            self.refresh.check()?;
             self.display_time()?;
+            self.update_alarms()?;
+
+            let millis = self.now().subsec_millis();
+            let mut wait = self.separator
+                .until_next_edge(millis)
+                .map_or(UPDATE_PERIOD, |edge| edge.min(UPDATE_PERIOD));
+            // the flashing display, buzzer and unsynchronized indicator change every half second
+            if self.alarm.is_some() || (self.sync_check && self.sync == SyncStatus::Unsynchronized) {
+                wait = wait.min(Duration::from_millis((500 - millis % 500) as u64));
+            }
+            if self.alarm.as_ref().is_some_and(|(_, outputs)| outputs.has_button()) {
+                wait = wait.min(BUTTON_POLL_PERIOD);
+            }
+            self.shutdown.sleep(wait);
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert hours from 0 to 23 to 12-hour format: midnight is 12 AM, noon is 12 PM.
+///
+/// # Returns
+/// * `(u8, bool)` - The hours from 1 to 12, and whether the time is PM.
+fn twelve_hour(hours: u8) -> (u8, bool) {
+    match hours % 12 {
+        0 => (12, hours >= 12),
+        h => (h, hours >= 12),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use bbb_hal::{gpio::mock::MockBackend, header::HeaderPin, multiplex::Digits, seven_segment::Segment, Value};
+
+    use super::*;
+    use crate::{
+        alarm::{Alarm, AlarmConfig, Repeat, LONG_PRESS},
+        time_source::{FixedClock, ManualClock},
+    };
+
+    /// 2026-10-17 00:00:00 UTC.
+    const MIDNIGHT: u64 = 1_792_195_200;
+
+    fn clock_at(format: ClockFormat, hour: u64) -> DigitalClock {
+        let display = MultiplexedDisplay::with_backend(Box::new(MockBackend::new()), Wiring::default()).unwrap();
+        let mut clock = DigitalClock::with_display(display, format, TimeZone::utc(), ScanTiming::default(), None, Shutdown::new()).unwrap();
+        clock.set_separator(Separator::Off);
+        clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + hour * 3600)));
+        clock
+    }
+
+    fn shown(clock: &mut DigitalClock) -> Frame {
+        clock.display_time().unwrap();
+        clock.buffer.get()
+    }
+
+    #[test]
+    fn twelve_hour_boundaries() {
+        assert_eq!(twelve_hour(0), (12, false));
+        assert_eq!(twelve_hour(11), (11, false));
+        assert_eq!(twelve_hour(12), (12, true));
+        assert_eq!(twelve_hour(13), (1, true));
+        assert_eq!(twelve_hour(23), (11, true));
+    }
+
+    #[test]
+    fn twelve_hour_frames() {
+        let cases = [(0, "1200", false), (11, "1100", false), (12, "1200", true), (13, " 100", true), (23, "1100", true)];
+
+        for (hour, text, pm) in cases {
+            let mut clock = clock_at(ClockFormat::TwelveHour, hour);
+
+            let time = clock.get_current_time();
+            assert_eq!((time.hours, time.pm), (twelve_hour(hour as u8).0, Some(pm)), "hour {}", hour);
+
+            let mut expected = bbb_hal::text::string(text).unwrap();
+            expected[PM_DIGIT] = expected[PM_DIGIT].with_dp(pm);
+            let frame = shown(&mut clock);
+            assert_eq!(frame, expected, "hour {}", hour);
+
+            // single-digit hours are blanked, not zero-padded
+            assert_eq!(frame[0] == Glyph::BLANK, time.hours < 10, "hour {}", hour);
+            clock.stop().unwrap();
+        }
+    }
+
+    #[test]
+    fn twenty_four_hour_frames() {
+        for (hour, text) in [(0, "0000"), (11, "1100"), (12, "1200"), (13, "1300"), (23, "2300")] {
+            let mut clock = clock_at(ClockFormat::TwentyFourHour, hour);
+            assert_eq!(clock.get_current_time().pm, None);
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(text).unwrap(), "hour {}", hour);
+            clock.stop().unwrap();
+        }
+    }
+
+    #[test]
+    fn minute_second_frames() {
+        for format in [ClockFormat::TwelveHour, ClockFormat::TwentyFourHour] {
+            let mut clock = clock_at(format, 13);
+            clock.set_mode(DisplayMode::MinuteSecond);
+            clock.set_separator(Separator::On);
+            clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + 7 * 60 + 42)));
+
+            // no hours, so no PM dot either
+            assert_eq!(shown(&mut clock), bbb_hal::text::string("07.42").unwrap(), "{:?}", format);
+            clock.stop().unwrap();
+        }
+    }
+
+    #[test]
+    fn hour_minute_second_scrolls_every_six_seconds() {
+        let mut clock = clock_at(ClockFormat::TwentyFourHour, 13);
+        clock.set_mode(DisplayMode::HourMinuteSecond);
+        clock.set_separator(Separator::On);
+
+        // hours and minutes for three seconds, one digit over, then minutes and seconds
+        let cases = [(36, "13.07."), (37, "13.07."), (38, "13.07."), (39, "3.07.3"), (40, "07.40"), (41, "07.41"), (42, "13.07.")];
+        for (seconds, text) in cases {
+            clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + 7 * 60 + seconds)));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(text).unwrap(), "second {}", seconds);
+        }
+
+        clock.stop().unwrap();
+
+        // the blanked leading zero of 12-hour format scrolls out too
+        let mut clock = clock_at(ClockFormat::TwelveHour, 13);
+        clock.set_mode(DisplayMode::HourMinuteSecond);
+        clock.set_am_pm(AmPmIndicator::Off);
+        for (seconds, text) in [(36, " 107"), (39, "1073"), (40, "0740")] {
+            clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + 7 * 60 + seconds)));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(text).unwrap(), "second {}", seconds);
+        }
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn no_pm_dot_while_scrolling() {
+        let mut clock = clock_at(ClockFormat::TwelveHour, 13);
+        clock.set_mode(DisplayMode::HourMinuteSecond);
+        clock.set_separator(Separator::On);
+        clock.set_am_pm(AmPmIndicator::Dot);
+
+        // the separators only, wherever the window is
+        for (seconds, text) in [(36, " 1.07."), (39, "1.07.3"), (40, "07.40")] {
+            clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + 7 * 60 + seconds)));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(text).unwrap(), "second {}", seconds);
+        }
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn separator_phase() {
+        // lit from every second boundary
+        for (millis, blink, double) in [(0, true, true), (149, true, true), (150, true, false), (300, true, true), (450, true, false), (499, true, false), (500, false, false), (999, false, false)] {
+            assert_eq!(Separator::Blink.is_lit(millis), blink, "{} ms", millis);
+            assert_eq!(Separator::DoubleBlink.is_lit(millis), double, "{} ms", millis);
+        }
+        assert!((0..1000).all(|millis| Separator::On.is_lit(millis) && !Separator::Off.is_lit(millis)));
+    }
+
+    #[test]
+    fn separator_edges() {
+        let ms = Duration::from_millis;
+        assert_eq!(Separator::Blink.until_next_edge(0), Some(ms(500)));
+        assert_eq!(Separator::Blink.until_next_edge(499), Some(ms(1)));
+        // the next edge is the following second boundary
+        assert_eq!(Separator::Blink.until_next_edge(500), Some(ms(500)));
+        assert_eq!(Separator::Blink.until_next_edge(999), Some(ms(1)));
+        assert_eq!(Separator::DoubleBlink.until_next_edge(100), Some(ms(50)));
+        assert_eq!(Separator::DoubleBlink.until_next_edge(450), Some(ms(550)));
+        assert_eq!(Separator::On.until_next_edge(0), None);
+        assert_eq!(Separator::Off.until_next_edge(500), None);
+    }
+
+    #[test]
+    fn separator_blinks_with_the_time_source() {
+        let mut clock = clock_at(ClockFormat::TwentyFourHour, 13);
+        clock.set_separator(Separator::Blink);
+
+        let at = |millis: u64| FixedClock { time: SystemTime::UNIX_EPOCH + Duration::from_millis((MIDNIGHT + 13 * 3600) * 1000 + millis) };
+        for (millis, text) in [(0, "13.00"), (499, "13.00"), (500, "1300"), (999, "1300"), (1000, "13.00")] {
+            clock.set_time_source(Box::new(at(millis)));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(text).unwrap(), "{} ms", millis);
+        }
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn cycle_parse() {
+        let secs = Duration::from_secs;
+        assert_eq!(CycleSchedule::parse("20,3,2").unwrap(), CycleSchedule { time: secs(20), date: secs(3), year: secs(2) });
+        assert_eq!(CycleSchedule::parse(" 10 , 0 , 5 ").unwrap(), CycleSchedule { time: secs(10), date: secs(0), year: secs(5) });
+        assert_eq!(CycleSchedule::parse("0,4,0").unwrap().period(), secs(4));
+
+        for spec in ["", "20", "20,3", "20,3,2,1", "0,0,0", "20,x,2", "-1,3,2", "2.5,3,2"] {
+            let error = CycleSchedule::parse(spec).unwrap_err();
+            assert_eq!(error.to_string(), format!("Invalid cycle, expected <time>,<date>,<year> in seconds: {}", spec));
+        }
+    }
+
+    #[test]
+    fn cycle_views_follow_the_epoch() {
+        let cycle = CycleSchedule::parse("20,3,2").unwrap();
+        // MIDNIGHT is a multiple of the 25 s period
+        let at = |secs: u64, millis: u64| Duration::from_millis((MIDNIGHT + secs) * 1000 + millis);
+
+        for (secs, millis, view) in [(0, 0, View::Time), (19, 999, View::Time), (20, 0, View::Date), (22, 999, View::Date), (23, 0, View::Year), (24, 999, View::Year), (25, 0, View::Time), (45, 0, View::Date)] {
+            assert_eq!(cycle.view_at(at(secs, millis)), view, "{}.{:03} s", secs, millis);
+        }
+        assert!((0..100).all(|secs| CycleSchedule::default().view_at(at(secs, 0)) == View::Time));
+    }
+
+    #[test]
+    fn date_and_year_views() {
+        let mut clock = clock_at(ClockFormat::TwelveHour, 13);
+        clock.set_cycle(CycleSchedule::parse("20,3,2").unwrap());
+        let at = |secs: u64| Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + secs));
+
+        // 2026-10-17
+        for (order, date) in [(DateOrder::DayMonth, "17.10"), (DateOrder::MonthDay, "10.17")] {
+            clock.set_date_order(order);
+            clock.set_time_source(at(21));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string(date).unwrap(), "{:?}", order);
+            clock.set_time_source(at(24));
+            assert_eq!(shown(&mut clock), bbb_hal::text::string("2026").unwrap(), "{:?}", order);
+        }
+
+        // the date of the time zone, not of UTC
+        clock.zone = TimeZone::from_posix("EST5").unwrap();
+        clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 3 * 3600 + 21)));
+        assert_eq!(shown(&mut clock), bbb_hal::text::string("10.16").unwrap());
+
+        // and the time again from the start of the next cycle
+        clock.zone = TimeZone::utc();
+        clock.set_time_source(at(25));
+        let mut expected = bbb_hal::text::string(" 100").unwrap();
+        expected[PM_DIGIT] = expected[PM_DIGIT].with_dp(true);
+        assert_eq!(shown(&mut clock), expected);
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn unset_clock_shows_dashes() {
+        let mut clock = clock_at(ClockFormat::TwentyFourHour, 13);
+        clock.set_cycle(CycleSchedule::parse("20,3,2").unwrap());
+        clock.sync = SyncStatus::Unset;
+
+        // whatever the view
+        for secs in [0, 21, 24] {
+            clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600 + secs)));
+            // a time source turns the check off
+            clock.set_sync_check(true);
+            clock.show_time().unwrap();
+            assert_eq!(clock.buffer.get(), bbb_hal::text::string("----").unwrap(), "{} s", secs);
         }
+
+        // not checked, the time as it is
+        clock.set_sync_check(false);
+        clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600)));
+        clock.show_time().unwrap();
+        assert_eq!(clock.buffer.get(), bbb_hal::text::string("1300").unwrap());
+        clock.stop().unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn unsynchronized_clock_blinks_the_first_dot() {
+        let mut clock = clock_at(ClockFormat::TwentyFourHour, 13);
+        clock.set_separator(Separator::Blink);
+        let at = |millis: u64| FixedClock { time: SystemTime::UNIX_EPOCH + Duration::from_millis((MIDNIGHT + 13 * 3600) * 1000 + millis) };
+
+        // lit in the second half of every second, opposite to the separator
+        let cases = [
+            (SyncStatus::Unsynchronized, 0, "13.00"),
+            (SyncStatus::Unsynchronized, 499, "13.00"),
+            (SyncStatus::Unsynchronized, 500, "1.300"),
+            (SyncStatus::Unsynchronized, 999, "1.300"),
+            (SyncStatus::Synchronized, 0, "13.00"),
+            (SyncStatus::Synchronized, 500, "1300"),
+        ];
+        for (status, millis, text) in cases {
+            clock.sync = status;
+            clock.set_time_source(Box::new(at(millis)));
+            clock.set_sync_check(true);
+            clock.show_time().unwrap();
+            assert_eq!(clock.buffer.get(), bbb_hal::text::string(text).unwrap(), "{:?} at {} ms", status, millis);
+        }
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn display_time_is_scanned_onto_the_pins() {
+        let mock = MockBackend::new();
+        let display = MultiplexedDisplay::with_backend(Box::new(mock.clone()), Wiring::default()).unwrap();
+        let mut clock = DigitalClock::with_display(display, ClockFormat::TwentyFourHour, TimeZone::utc(), ScanTiming::default(), None, Shutdown::new()).unwrap();
+        clock.set_separator(Separator::On);
+        clock.set_time_source(Box::new(FixedClock::at(MIDNIGHT + 13 * 3600)));
+
+        clock.display_time().unwrap();
+        mock.clear_writes();
+        // a few full scans of the new frame
+        while mock.writes().len() < 200 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        clock.stop().unwrap();
+
+        // every enabled digit is followed by the write of its glyph
+        let wiring = Wiring::default();
+        let digits = Digits::ALL.map(|dig| (wiring.digit(dig).gpio(), dig));
+        let segments = Segment::ALL.map(|seg| wiring.segment(seg).gpio());
+        let writes = mock.writes();
+        let mut expected = bbb_hal::text::string("1300").unwrap();
+        expected[SEPARATOR_DIGIT] = expected[SEPARATOR_DIGIT].with_dp(true);
+
+        let mut checked = 0;
+        for window in writes.windows(DIGITS + 8) {
+            let (enable, glyph) = window.split_at(DIGITS);
+            let lit = enable.iter().filter(|(_, value)| *value == Value::Active).collect::<Vec<_>>();
+            if glyph.iter().map(|(pin, _)| *pin).ne(segments) || lit.len() != 1 {
+                continue;
+            }
+            let (_, dig) = digits.iter().find(|(pin, _)| *pin == lit[0].0).unwrap();
+            let index = Digits::ALL.iter().position(|d| d == dig).unwrap();
+            let shown = Segment::ALL
+                .into_iter()
+                .zip(glyph)
+                .fold(Glyph::BLANK, |glyph, (seg, (_, value))| glyph.with_segment(seg, *value == Value::Active));
+            assert_eq!(shown, expected[index], "digit {}", index);
+            checked += 1;
+        }
+        assert!(checked >= DIGITS);
+    }
+
+    /// One pass of the main loop, without the wait.
+    fn tick(clock: &mut DigitalClock) -> AlarmState {
+        clock.display_time().unwrap();
+        clock.update_alarms().unwrap();
+        clock.alarm.as_ref().unwrap().0.state()
+    }
+
+    #[test]
+    fn alarm_is_snoozed_and_dismissed_with_the_button() {
+        let mock = MockBackend::new();
+        let (buzzer, button) = (HeaderPin::P8_26, HeaderPin::P8_15);
+        let config = AlarmConfig { buzzer: Some(buzzer), button: Some(button), ..AlarmConfig::default() };
+        let outputs = AlarmOutputs::with_backend(Box::new(mock.clone()), &config, &Wiring::default(), None).unwrap();
+        let alarm = Alarm { hour: 6, minute: 45, repeat: Repeat::Daily, date: None };
+
+        let source = ManualClock::at(MIDNIGHT + 6 * 3600 + 44 * 60);
+        let mut clock = clock_at(ClockFormat::TwentyFourHour, 0);
+        clock.set_time_source(Box::new(source.clone()));
+        clock.set_alarms(AlarmClock::new(vec![alarm], config.snooze, config.ring_timeout), outputs);
+        let buzzing = || mock.is_high(buzzer.gpio()) == Some(true);
+        let press = |clock: &mut DigitalClock, held: Duration| {
+            mock.set_level(button.gpio(), Value::Active);
+            tick(clock);
+            thread::sleep(held);
+            tick(clock);
+            mock.set_level(button.gpio(), Value::Inactive);
+            tick(clock)
+        };
+
+        assert_eq!(tick(&mut clock), AlarmState::Idle);
+        assert!(!buzzing());
+
+        // on with the time in the first half of every second, off and blank in the second half
+        source.advance(Duration::from_secs(60));
+        let at = (MIDNIGHT + 6 * 3600 + 45 * 60) as i64;
+        assert_eq!(tick(&mut clock), AlarmState::Ringing { since: at });
+        assert!(buzzing());
+        assert_eq!(clock.buffer.get(), bbb_hal::text::string("0645").unwrap());
+        source.advance(Duration::from_millis(500));
+        tick(&mut clock);
+        assert!(!buzzing());
+        assert_eq!(clock.buffer.get(), [Glyph::BLANK; DIGITS]);
+
+        let until = at + config.snooze.as_secs() as i64;
+        assert_eq!(press(&mut clock, Duration::ZERO), AlarmState::Snoozed { until });
+        assert!(!buzzing());
+
+        source.advance(config.snooze + Duration::from_millis(500));
+        assert_eq!(tick(&mut clock), AlarmState::Ringing { since: until });
+        assert!(buzzing());
+
+        assert_eq!(press(&mut clock, LONG_PRESS), AlarmState::Idle);
+        assert!(!buzzing());
+        assert_eq!(clock.buffer.get(), bbb_hal::text::string("0654").unwrap());
+
+        clock.stop().unwrap();
+    }
+
+    #[test]
+    fn day_of_minutes_rolls_over() {
+        let source = ManualClock::at(MIDNIGHT);
+        let mut twelve = clock_at(ClockFormat::TwelveHour, 0);
+        let mut twenty_four = clock_at(ClockFormat::TwentyFourHour, 0);
+        twelve.set_time_source(Box::new(source.clone()));
+        twenty_four.set_time_source(Box::new(source.clone()));
+        twelve.set_am_pm(AmPmIndicator::Off);
+
+        let mut frames = Vec::new();
+        for _ in 0..=24 * 60 {
+            frames.push((shown(&mut twelve), shown(&mut twenty_four)));
+            source.advance(Duration::from_secs(60));
+        }
+
+        // every minute of the day, then midnight again
+        for (minute, (twelve, twenty_four)) in frames.iter().enumerate() {
+            let (hours, minutes) = (minute / 60 % 24, minute % 60);
+            let expected = bbb_hal::text::string(&format!("{:02}{:02}", hours, minutes)).unwrap();
+            assert_eq!(*twenty_four, expected, "minute {}", minute);
+            let expected = bbb_hal::text::string(&format!("{:>2}{:02}", twelve_hour(hours as u8).0, minutes)).unwrap();
+            assert_eq!(*twelve, expected, "minute {}", minute);
+        }
+
+        // the rollovers, spelled out
+        let text = |text: &str| bbb_hal::text::string(text).unwrap();
+        for (minute, before, after) in [(59, "1259", " 100"), (9 * 60 + 59, " 959", "1000"), (12 * 60 + 59, "1259", " 100"), (24 * 60 - 1, "1159", "1200")] {
+            assert_eq!((frames[minute].0, frames[minute + 1].0), (text(before), text(after)), "minute {}", minute);
+        }
+        for (minute, before, after) in [(9 * 60 + 59, "0959", "1000"), (24 * 60 - 1, "2359", "0000")] {
+            assert_eq!((frames[minute].1, frames[minute + 1].1), (text(before), text(after)), "minute {}", minute);
+        }
+
+        twelve.stop().unwrap();
+        twenty_four.stop().unwrap();
+    }
+}
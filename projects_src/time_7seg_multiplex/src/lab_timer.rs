@@ -0,0 +1,495 @@
+use std::{
+    error::Error,
+    io::{self, BufRead},
+    process::{Child, Command},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bbb_hal::{
+    font::Glyph,
+    led::UserLed,
+    multiplex::{Frame, FrameBuffer, MultiplexedDisplay, ScanTiming, DIGITS, SEPARATOR_DIGIT},
+    realtime::{JitterStats, RealtimeConfig},
+    refresh::RefreshThread,
+    shutdown::Shutdown,
+    text,
+};
+
+/// Time between two updates of the frame buffer, short enough for the hundredths.
+const UPDATE_PERIOD: Duration = Duration::from_millis(10);
+
+/// Time a lap time stays on the display.
+const LAP_HOLD: Duration = Duration::from_secs(2);
+
+/// Commands typed on the standard input, one per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Start, pause or resume.
+    Toggle,
+    /// Record a lap time.
+    Lap,
+    /// Stop and go back to the start.
+    Reset,
+    /// Leave the program.
+    Quit,
+}
+
+impl Control {
+    /// Parse a line of input: empty or `p` toggles, `l` records a lap, `r` resets and `q` quits.
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "" | "p" => Some(Control::Toggle),
+            "l" => Some(Control::Lap),
+            "r" => Some(Control::Reset),
+            "q" => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// A stopwatch that can be paused and records lap times.
+///
+/// Every method takes the current instant, so the stopwatch can be driven
+/// by a simulated time in tests.
+///
+/// # Fields
+/// * `started` - The instant the stopwatch was last started or resumed, `None` while paused.
+/// * `accumulated` - The time counted before the last start.
+/// * `laps` - The elapsed time at every lap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    accumulated: Duration,
+    laps: Vec<Duration>,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Time counted up to an instant.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.accumulated + self.started.map_or(Duration::ZERO, |started| now.saturating_duration_since(started))
+    }
+
+    /// Start or resume counting.
+    pub fn start(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    /// Stop counting, keeping the elapsed time.
+    pub fn pause(&mut self, now: Instant) {
+        self.accumulated = self.elapsed(now);
+        self.started = None;
+    }
+
+    /// Pause a running stopwatch, or start a paused one.
+    pub fn toggle(&mut self, now: Instant) {
+        if self.is_running() {
+            self.pause(now);
+        } else {
+            self.start(now);
+        }
+    }
+
+    /// Stop counting and forget the elapsed time and laps.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a lap.
+    ///
+    /// # Returns
+    /// * `Duration` - The time since the previous lap, or since the start for the first one.
+    pub fn lap(&mut self, now: Instant) -> Duration {
+        let elapsed = self.elapsed(now);
+        let previous = self.laps.last().copied().unwrap_or_default();
+        self.laps.push(elapsed);
+        elapsed.saturating_sub(previous)
+    }
+
+    pub fn laps(&self) -> &[Duration] {
+        &self.laps
+    }
+}
+
+/// Render a duration: `SS.hh` below a minute, `MM.SS` below 100 minutes and `HH.MM.` above,
+/// the second decimal point telling hours and minutes from minutes and seconds.
+///
+/// # Arguments
+/// * `time` - The duration to render.
+///
+/// # Returns
+/// * `Result<Frame, Box<dyn Error>>` - The rendered digits, [`text::OVERFLOW`] from 100 hours.
+pub fn render(time: Duration) -> Result<Frame, Box<dyn Error>> {
+    let secs = time.as_secs();
+
+    let (high, low, hours) = match secs {
+        0..=59 => (secs, time.subsec_millis() as u64 / 10, false),
+        60..=5999 => (secs / 60, secs % 60, false),
+        6000..=359_999 => (secs / 3600, secs / 60 % 60, true),
+        _ => return Ok(text::OVERFLOW),
+    };
+
+    let mut frame = text::string(&format!("{:2}{:02}", high, low))?;
+    frame[SEPARATOR_DIGIT] = frame[SEPARATOR_DIGIT].with_dp(true);
+    frame[DIGITS - 1] = frame[DIGITS - 1].with_dp(hours);
+
+    Ok(frame)
+}
+
+/// Format a duration as `MM:SS.hh` for the terminal.
+pub fn format_duration(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{:02}:{:02}.{:02}", secs / 60, secs % 60, time.subsec_millis() / 10)
+}
+
+/// Parse a countdown length, given as seconds, `MM:SS` or `HH:MM:SS`.
+///
+/// # Arguments
+/// * `spec` - The length.
+///
+/// # Returns
+/// * `Result<Duration, Box<dyn Error>>` - The length, or an error if it is invalid or zero.
+pub fn parse_length(spec: &str) -> Result<Duration, Box<dyn Error>> {
+    let invalid = || format!("Invalid timer length, expected seconds, MM:SS or HH:MM:SS: {}", spec);
+
+    let parts = spec
+        .split(':')
+        .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if parts.len() > 3 || parts[1..].iter().any(|&part| part >= 60) {
+        return Err(invalid().into());
+    }
+
+    let secs = parts.iter().fold(0, |total, part| total * 60 + part);
+    if secs == 0 {
+        return Err(invalid().into());
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// What happens when a countdown reaches zero, besides the flashing display.
+///
+/// # Fields
+/// * `led` - The user LED blinking with the display, if any.
+/// * `command` - The shell command run once, if any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpiryAction {
+    pub led: Option<u8>,
+    pub command: Option<String>,
+}
+
+/// Whether the lab timer counts up or down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimerMode {
+    /// Count up from zero.
+    Stopwatch,
+    /// Count down from a length.
+    Countdown(Duration),
+}
+
+/// A countdown timer or stopwatch on the multiplexed display, controlled from the standard input.
+///
+/// # Fields
+/// * `refresh` - The thread scanning the display.
+/// * `buffer` - The frame shown by the refresh thread.
+/// * `mode` - Whether the timer counts up or down.
+/// * `watch` - The elapsed time.
+/// * `expiry` - What happens when a countdown reaches zero.
+/// * `led` - The user LED of the expiry action, taken when it is set.
+/// * `controls` - The commands read from the standard input.
+/// * `shutdown` - The flag stopping the timer.
+pub struct LabTimer {
+    refresh: RefreshThread,
+    buffer: FrameBuffer,
+    mode: TimerMode,
+    watch: Stopwatch,
+    expiry: ExpiryAction,
+    led: Option<UserLed>,
+    controls: Receiver<Control>,
+    shutdown: Shutdown,
+}
+
+impl LabTimer {
+    pub fn new(display: MultiplexedDisplay, mode: TimerMode, timing: ScanTiming, realtime: Option<RealtimeConfig>, shutdown: Shutdown) -> Result<Self, Box<dyn Error>> {
+        let refresh = match realtime {
+            Some(config) => RefreshThread::spawn_realtime(display, timing, config)?,
+            None => RefreshThread::spawn(display, timing)?,
+        };
+        let buffer = refresh.buffer();
+
+        // the reader thread is left blocked on stdin when the timer stops
+        let (sender, controls) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                match Control::parse(&line) {
+                    Some(control) => if sender.send(control).is_err() { break },
+                    None => eprintln!("Unknown command: {:?} (Enter/p: start/pause, l: lap, r: reset, q: quit)", line),
+                }
+            }
+        });
+
+        Ok(Self {
+            refresh,
+            buffer,
+            mode,
+            watch: Stopwatch::new(),
+            expiry: ExpiryAction::default(),
+            led: None,
+            controls,
+            shutdown,
+        })
+    }
+
+    /// Start counting now rather than on the first Enter.
+    pub fn start(&mut self) {
+        self.watch.start(Instant::now());
+    }
+
+    /// Set what happens when the countdown reaches zero, taking the user LED now
+    /// so a wrong LED number is reported before the timer starts.
+    ///
+    /// # Arguments
+    /// * `expiry` - The expiry action.
+    ///
+    /// # Returns
+    /// * `Result<(), Box<dyn Error>>` - An error if the user LED cannot be opened.
+    pub fn set_expiry(&mut self, expiry: ExpiryAction) -> Result<(), Box<dyn Error>> {
+        self.led = expiry.led.map(UserLed::open).transpose()?;
+        self.expiry = expiry;
+        Ok(())
+    }
+
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Box<dyn Error>> {
+        self.refresh.brightness().set(level)
+    }
+
+    pub fn stop(self) -> Result<Option<JitterStats>, Box<dyn Error>> {
+        let jitter = self.refresh.jitter();
+        self.refresh.stop()?;
+        Ok(jitter)
+    }
+
+    /// The time shown: the elapsed time of a stopwatch, the remaining time of a countdown.
+    fn shown(&self, now: Instant) -> Duration {
+        let elapsed = self.watch.elapsed(now);
+        match self.mode {
+            TimerMode::Stopwatch => elapsed,
+            TimerMode::Countdown(length) => length.saturating_sub(elapsed),
+        }
+    }
+
+    /// Handle the pending commands.
+    ///
+    /// # Returns
+    /// * `Result<bool, Box<dyn Error>>` - `false` when the user quit.
+    fn handle_controls(&mut self, now: Instant, held: &mut Option<(Frame, Instant)>) -> Result<bool, Box<dyn Error>> {
+        loop {
+            let control = match self.controls.try_recv() {
+                Ok(control) => control,
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return Ok(true),
+            };
+
+            match control {
+                Control::Toggle => self.watch.toggle(now),
+                Control::Lap if self.watch.is_running() => {
+                    let split = self.watch.lap(now);
+                    println!("Lap {}: {} (total {})", self.watch.laps().len(), format_duration(split), format_duration(self.watch.elapsed(now)));
+                    *held = Some((render(split)?, now + LAP_HOLD));
+                },
+                Control::Lap => {},
+                Control::Reset => {
+                    self.watch.reset();
+                    *held = None;
+                },
+                Control::Quit => return Ok(false),
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut held: Option<(Frame, Instant)> = None;
+        let mut expired: Option<(Instant, Option<Child>)> = None;
+
+        println!("Enter or p: start/pause, l: lap, r: reset, q: quit");
+
+        while !self.shutdown.is_requested() {
+            self.refresh.check()?;
+
+            let now = Instant::now();
+            if !self.handle_controls(now, &mut held)? {
+                break;
+            }
+
+            let shown = self.shown(now);
+            let mut frame = match held {
+                Some((frame, until)) if now < until => frame,
+                _ => render(shown)?,
+            };
+
+            let is_expired = matches!(self.mode, TimerMode::Countdown(_)) && shown.is_zero();
+            match (&mut expired, is_expired) {
+                (None, true) => {
+                    println!("Time is up");
+                    let child = self.expiry.command
+                        .as_deref()
+                        .map(|command| Command::new("sh").arg("-c").arg(command).spawn())
+                        .transpose()
+                        .map_err(|e| format!("Cannot run expiry command: {}", e))?;
+                    expired = Some((now, child));
+                },
+                (Some((since, _)), true) => {
+                    // display and LED flash at 2 Hz
+                    let on = (now - *since).as_millis() % 500 < 250;
+                    if !on {
+                        frame = [Glyph::BLANK; DIGITS];
+                    }
+                    if let Some(led) = &mut self.led {
+                        led.set(on)?;
+                    }
+                },
+                // reset after expiry
+                (Some(_), false) => {
+                    expired = None;
+                    if let Some(led) = &mut self.led {
+                        led.set(false)?;
+                    }
+                },
+                (None, false) => {},
+            }
+
+            // reap the expiry command once it exits
+            if let Some((_, command)) = &mut expired {
+                if let Some(status) = command.as_mut().map(Child::try_wait).transpose()?.flatten() {
+                    if !status.success() {
+                        eprintln!("Expiry command failed: {}", status);
+                    }
+                    *command = None;
+                }
+            }
+
+            self.buffer.set(frame);
+            self.shutdown.sleep(UPDATE_PERIOD);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn control_parse() {
+        for (line, control) in [("", Control::Toggle), ("  \n", Control::Toggle), ("p", Control::Toggle), (" l ", Control::Lap), ("r", Control::Reset), ("q\n", Control::Quit)] {
+            assert_eq!(Control::parse(line), Some(control), "{:?}", line);
+        }
+        for line in ["s", "P", "pause", "x", "lr"] {
+            assert_eq!(Control::parse(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn stopwatch_start_pause_resume() {
+        let start = Instant::now();
+        let mut watch = Stopwatch::new();
+        assert!(!watch.is_running());
+        assert_eq!(watch.elapsed(start + secs(5)), Duration::ZERO);
+
+        watch.start(start);
+        assert!(watch.is_running());
+        assert_eq!(watch.elapsed(start + Duration::from_millis(1500)), Duration::from_millis(1500));
+        // starting again does not restart
+        watch.start(start + secs(1));
+        assert_eq!(watch.elapsed(start + secs(2)), secs(2));
+
+        watch.pause(start + secs(2));
+        assert!(!watch.is_running());
+        assert_eq!(watch.elapsed(start + secs(10)), secs(2));
+
+        watch.toggle(start + secs(10));
+        assert_eq!(watch.elapsed(start + secs(11)), secs(3));
+        watch.toggle(start + secs(11));
+        assert_eq!(watch.elapsed(start + secs(20)), secs(3));
+
+        watch.reset();
+        assert_eq!(watch, Stopwatch::new());
+    }
+
+    #[test]
+    fn stopwatch_laps() {
+        let start = Instant::now();
+        let mut watch = Stopwatch::new();
+        watch.start(start);
+
+        assert_eq!(watch.lap(start + secs(10)), secs(10));
+        assert_eq!(watch.lap(start + secs(25)), secs(15));
+        // the time paused does not count
+        watch.pause(start + secs(30));
+        watch.start(start + secs(60));
+        assert_eq!(watch.lap(start + secs(62)), secs(7));
+        assert_eq!(watch.laps(), [secs(10), secs(25), secs(32)]);
+
+        watch.reset();
+        assert!(watch.laps().is_empty());
+    }
+
+    #[test]
+    fn render_ranges() {
+        let millis = Duration::from_millis;
+        let cases = [
+            (Duration::ZERO, " 0.00"),
+            (millis(9_500), " 9.50"),
+            (millis(59_999), "59.99"),
+            (secs(60), " 1.00"),
+            (secs(5999), "99.59"),
+            // 1 h 40 min, the second dot marking the hours
+            (secs(6000), " 1.40."),
+            (secs(359_999), "99.59."),
+        ];
+        for (time, text) in cases {
+            assert_eq!(render(time).unwrap(), text::string(text).unwrap(), "{:?}", time);
+        }
+
+        assert_eq!(render(secs(100 * 3600)).unwrap(), text::OVERFLOW);
+        assert_eq!(render(secs(u64::MAX)).unwrap(), text::OVERFLOW);
+    }
+
+    #[test]
+    fn format_duration_counts_minutes() {
+        assert_eq!(format_duration(Duration::ZERO), "00:00.00");
+        assert_eq!(format_duration(Duration::from_millis(61_239)), "01:01.23");
+        assert_eq!(format_duration(secs(6000)), "100:00.00");
+    }
+
+    #[test]
+    fn parse_length_formats() {
+        assert_eq!(parse_length("90").unwrap(), secs(90));
+        assert_eq!(parse_length("1:30").unwrap(), secs(90));
+        assert_eq!(parse_length("01:00:05").unwrap(), secs(3605));
+        assert_eq!(parse_length("120:00").unwrap(), secs(7200));
+    }
+
+    #[test]
+    fn parse_length_rejects_bad_input() {
+        for spec in ["", "0", "0:00", "1:60", "1:00:60", "1:2:3:4", "-5", "1.5", "a:10", ":30", "1:", " 1", "1m"] {
+            let error = parse_length(spec).unwrap_err();
+            assert_eq!(error.to_string(), format!("Invalid timer length, expected seconds, MM:SS or HH:MM:SS: {}", spec));
+        }
+    }
+}
@@ -1,46 +1,294 @@
-use std::env;
+use std::{env, path::PathBuf, time::{Duration, SystemTime}};
 
-mod seven_segment;
+use bbb_hal::{
+    brightness::MAX_BRIGHTNESS,
+    multiplex::{MultiplexedDisplay, ScanTiming},
+    realtime::RealtimeConfig,
+    refresh,
+    rtc::Ds3231,
+    shutdown::Shutdown,
+    wiring::{self, Wiring},
+};
+
+mod alarm;
 mod digital_clock;
+mod lab_timer;
+mod time_source;
+mod time_zone;
 
-use digital_clock::{DigitalClock, ClockFormat};
+use alarm::{self as alarms, AlarmClock, AlarmConfig, AlarmOutputs};
+use digital_clock::{AmPmIndicator, CycleSchedule, DateOrder, DigitalClock, ClockFormat, DisplayMode, Separator};
+use lab_timer::{ExpiryAction, LabTimer, TimerMode};
+use time_source::{FixedClock, SimulatedClock};
+use time_zone::TimeZone;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let args = env::args().collect::<Vec<String>>();
     
-    if args.len() < 3 || args[1] == "-h" || args[1] == "--help" {
+    if args.len() < 2 || args[1] == "-h" || args[1] == "--help" {
         let usage_txt = format!(r#"Usage:
-    {} [option] <value>
+    {prog} [option] <value>                 wall clock
+    {prog} timer <length> [option] <value>  countdown from seconds, MM:SS or HH:MM:SS
+    {prog} stopwatch [option] <value>       stopwatch with lap times
+
+The timer and stopwatch start at once (see --start) and are controlled from
+the terminal: Enter or p pauses and resumes, l records a lap, r resets and q quits.
+They show SS.hh below a minute, MM.SS below 100 minutes, then HH.MM. with a
+second dot, and ---- from 100 hours.
 
 Options:
     -h, --help      print help and exit
     -f              clock format (12h or 24h)
-"#, args[0]);
+    -m              display mode (hhmm, mmss or hhmmss, default: hhmm)
+    -s              separator (blink, double, on or off, default: blink)
+    --ampm          PM indicator in 12h format (dot, flash or off, default: dot; no dot with hhmmss)
+    --cycle         seconds of time, date and year in the display cycle, e.g. 20,3,2 (default: time only)
+    --date-order    date order (dmy for DD.MM or mdy for MM.DD, default: dmy)
+    --fast-forward  demo mode, run the clock the given times faster than real time
+    --at            show a fixed time, given in seconds since the Unix epoch (start time with --fast-forward)
+    --sync-check    show ---- until the system clock is set and blink the first dot until it is synchronized (on or off, default: on)
+    --rtc           I2C bus of a DS3231 RTC restoring the clock at boot and saved once the clock is synchronized
+    --tz            time zone, e.g. Europe/Berlin (default: TZ, then /etc/localtime)
+    -r              display refresh rate in Hz (default: {})
+    --dwell         time every digit stays lit in µs (default: from the refresh rate)
+    --blanking      time the display stays blank between digits in µs (default: {})
+    --realtime      scan with SCHED_FIFO at the given priority (1-99), locked memory and absolute deadlines
+    --cpu           CPU the real-time scan is pinned to
+    --brightness    display brightness from 0 to {max} (default: {max})
+    --wiring        segment and digit wiring file (default: {})
+    --alarms        alarm file with the alarms, buzzer, LED and button (default: {} if present)
+    --led           timer: user LED (0-3) blinking when the time is up
+    --exec          timer: shell command run when the time is up
+    --start         timer and stopwatch: count from launch (on), or from the first Enter (off) (default: on)
+"#, refresh::DEFAULT_RATE_HZ,
+        ScanTiming::default().blanking.as_micros(), wiring::DEFAULT_PATH, alarms::DEFAULT_PATH, prog = args[0], max = MAX_BRIGHTNESS);
 
         println!("{}", usage_txt);
         return Ok(());
     }
 
-    if args.len() == 3 {
-        if args[1] == "-f" {
+    let mut clk_fmt = None;
+    let mut mode = DisplayMode::HourMinute;
+    let mut separator = Separator::Blink;
+    let mut am_pm = AmPmIndicator::Dot;
+    let mut cycle = CycleSchedule::default();
+    let mut date_order = DateOrder::DayMonth;
+    let mut wiring_path = None;
+    let mut alarm_path = None;
+    let mut sync_check = true;
+    let mut rtc_bus = None;
+    let mut zone = None;
+    let mut speed = None;
+    let mut at = None;
+    let mut refresh_hz = refresh::DEFAULT_RATE_HZ;
+    let mut dwell = None;
+    let mut blanking = None;
+    let mut brightness = MAX_BRIGHTNESS;
+    let mut rt_priority = None;
+    let mut cpu = None;
+
+    let (timer_mode, first_opt) = match args[1].as_str() {
+        "timer" => {
+            let length = args.get(2).ok_or("Missing timer length")?;
+            (Some(TimerMode::Countdown(lab_timer::parse_length(length)?)), 3)
+        },
+        "stopwatch" => (Some(TimerMode::Stopwatch), 2),
+        _ => (None, 1),
+    };
+    let mut expiry = ExpiryAction::default();
+    let mut start = None;
+
+    let mut opts = args[first_opt..].iter();
+    while let Some(opt) = opts.next() {
+        let value = opts.next().ok_or_else(|| format!("Missing value for option: {}", opt))?;
+
+        match opt.as_str() {
+            "-f" => {
+                clk_fmt = Some(match value.as_str() {
+                    "12h" => ClockFormat::TwelveHour,
+                    "24h" => ClockFormat::TwentyFourHour,
+                    _ => return Err(format!("Invalid clock format: {}", value).into()),
+                });
+            },
+            "-m" => {
+                mode = match value.as_str() {
+                    "hhmm" => DisplayMode::HourMinute,
+                    "mmss" => DisplayMode::MinuteSecond,
+                    "hhmmss" => DisplayMode::HourMinuteSecond,
+                    _ => return Err(format!("Invalid display mode: {}", value).into()),
+                };
+            },
+            "-s" => {
+                separator = match value.as_str() {
+                    "blink" => Separator::Blink,
+                    "double" => Separator::DoubleBlink,
+                    "on" => Separator::On,
+                    "off" => Separator::Off,
+                    _ => return Err(format!("Invalid separator: {}", value).into()),
+                };
+            },
+            "--ampm" => {
+                am_pm = match value.as_str() {
+                    "dot" => AmPmIndicator::Dot,
+                    "flash" => AmPmIndicator::Flash,
+                    "off" => AmPmIndicator::Off,
+                    _ => return Err(format!("Invalid AM/PM indicator: {}", value).into()),
+                };
+            },
+            "--cycle" => cycle = CycleSchedule::parse(value)?,
+            "--date-order" => {
+                date_order = match value.as_str() {
+                    "dmy" => DateOrder::DayMonth,
+                    "mdy" => DateOrder::MonthDay,
+                    _ => return Err(format!("Invalid date order: {}", value).into()),
+                };
+            },
+            "--fast-forward" => {
+                speed = Some(value.parse().ok().filter(|&speed| speed > 0).ok_or_else(|| format!("Invalid fast-forward factor: {}", value))?);
+            },
+            "--at" => {
+                at = Some(value.parse().map_err(|_| format!("Invalid Unix time: {}", value))?);
+            },
+            "--sync-check" => {
+                sync_check = match value.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("Invalid sync check: {}", value).into()),
+                };
+            },
+            "--rtc" => {
+                rtc_bus = Some(value.parse().map_err(|_| format!("Invalid I2C bus: {}", value))?);
+            },
+            "--tz" => zone = Some(TimeZone::named(value)?),
+            "-r" => {
+                refresh_hz = value.parse().map_err(|_| format!("Invalid refresh rate: {}", value))?;
+            },
+            "--dwell" => {
+                let micros = parse_micros(value)?;
+                if micros.is_zero() {
+                    return Err("Dwell time must be at least 1 µs".into());
+                }
+                dwell = Some(micros);
+            },
+            "--blanking" => blanking = Some(parse_micros(value)?),
+            "--realtime" => {
+                rt_priority = Some(value.parse().map_err(|_| format!("Invalid real-time priority: {}", value))?);
+            },
+            "--cpu" => {
+                cpu = Some(value.parse().map_err(|_| format!("Invalid CPU: {}", value))?);
+            },
+            "--brightness" => {
+                brightness = value.parse().map_err(|_| format!("Invalid brightness: {}", value))?;
+            },
+            "--wiring" => wiring_path = Some(PathBuf::from(value)),
+            "--alarms" => alarm_path = Some(PathBuf::from(value)),
+            "--led" => {
+                expiry.led = Some(value.parse().map_err(|_| format!("Invalid user LED: {}", value))?);
+            },
+            "--exec" => expiry.command = Some(value.clone()),
+            "--start" => {
+                start = match value.as_str() {
+                    "on" => Some(true),
+                    "off" => Some(false),
+                    _ => return Err(format!("Invalid value for --start: {}", value).into()),
+                };
+            },
+            _ => return Err(format!("Unknown option: {}", opt).into()),
+        }
+    }
+
+    let wiring = Wiring::load_or_default(wiring_path.as_deref())?;
+
+    let mut timing = ScanTiming::from_rate(refresh_hz)?;
+    timing.dwell = dwell.unwrap_or(timing.dwell);
+    timing.blanking = blanking.unwrap_or(timing.blanking);
+
+    if cpu.is_some() && rt_priority.is_none() {
+        return Err("--cpu needs --realtime".into());
+    }
+    let realtime = rt_priority.map(|priority| RealtimeConfig { priority, cpu, ..RealtimeConfig::default() });
 
-            let clk_fmt = match args[2].as_str() {
-                "12h" => ClockFormat::TwelveHour,
-                "24h" => ClockFormat::TwentyFourHour,
-                _ => return Err(format!("Invalid clock format: {}", args[2]).into()),
-            };
-            
-            let mut clock = DigitalClock::new(clk_fmt)?;
-            
-            println!("Starting digital clock in {} format. Press Ctrl+C to stop.", args[2]);
-            
-            clock.run()?;
+    if let Some(mode) = timer_mode {
+        if mode == TimerMode::Stopwatch && expiry != ExpiryAction::default() {
+            return Err("--led and --exec need the timer command".into());
+        }
+
+        let display = MultiplexedDisplay::with_wiring(wiring)?;
+        let mut timer = LabTimer::new(display, mode, timing, realtime, Shutdown::install()?)?;
+        timer.set_brightness(brightness)?;
+        timer.set_expiry(expiry)?;
+        if start.unwrap_or(true) {
+            timer.start();
+        }
+        timer.run()?;
 
-        } else {
-            return Err(format!("Unknown option: {}", args[1]).into());
+        if let Some(stats) = timer.stop()? {
+            println!("Scan jitter: {}", stats);
         }
+        return Ok(());
+    }
+
+    if expiry != ExpiryAction::default() {
+        return Err("--led and --exec need the timer command".into());
+    }
+    if start.is_some() {
+        return Err("--start needs the timer or stopwatch command".into());
+    }
+    let clk_fmt = clk_fmt.ok_or("Missing clock format, use -f 12h or -f 24h")?;
+    let alarm_config = AlarmConfig::load_or_none(alarm_path.as_deref())?;
+    let zone = match zone {
+        Some(zone) => zone,
+        None => TimeZone::local()?,
+    };
+
+    // the buzzer, button and LED stay free for other programs without alarms
+    let alarms = match alarm_config.filter(|config| !config.alarms.is_empty()) {
+        Some(config) => {
+            let outputs = AlarmOutputs::new(&config, &wiring)?;
+            Some((AlarmClock::new(config.alarms, config.snooze, config.ring_timeout), outputs))
+        },
+        None => None,
+    };
+
+    let mut clock = DigitalClock::new(clk_fmt, zone, wiring, timing, realtime, Shutdown::install()?)?;
+    clock.set_brightness(brightness)?;
+    clock.set_mode(mode);
+    clock.set_separator(separator);
+    clock.set_am_pm(am_pm);
+    clock.set_cycle(cycle);
+    clock.set_date_order(date_order);
+    clock.set_sync_check(sync_check);
+    if let Some(bus) = rtc_bus {
+        clock.set_rtc(Ds3231::open(bus)?)?;
+    }
+    match (speed, at.map(FixedClock::at)) {
+        (Some(speed), start) => {
+            let start = start.map_or_else(SystemTime::now, |fixed| fixed.time);
+            clock.set_time_source(Box::new(SimulatedClock::new(start, speed)));
+        },
+        (None, Some(fixed)) => clock.set_time_source(Box::new(fixed)),
+        (None, None) => {},
+    }
+    if let Some((alarms, outputs)) = alarms {
+        clock.set_alarms(alarms, outputs);
+    }
+
+    println!("Starting digital clock in {} format. Press Ctrl+C to stop.", match clk_fmt {
+        ClockFormat::TwelveHour => "12h",
+        ClockFormat::TwentyFourHour => "24h",
+    });
+
+    clock.run()?;
+
+    if let Some(stats) = clock.stop()? {
+        println!("Scan jitter: {}", stats);
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn parse_micros(value: &str) -> Result<Duration, Box<dyn std::error::Error>> {
+    let micros = value.parse().map_err(|_| format!("Invalid time in µs: {}", value))?;
+    Ok(Duration::from_micros(micros))
+}
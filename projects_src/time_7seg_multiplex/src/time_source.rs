@@ -0,0 +1,110 @@
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the current time shown by the clock.
+pub trait TimeSource: Send {
+    /// Get the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system real-time clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock standing still at a given time.
+///
+/// # Fields
+/// * `time` - The time returned by every call to [`TimeSource::now`].
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    pub time: SystemTime,
+}
+
+impl FixedClock {
+    /// Create a clock standing at some seconds after the Unix epoch.
+    ///
+    /// # Arguments
+    /// * `unix` - The seconds since 1970-01-01 00:00:00 UTC.
+    pub fn at(unix: u64) -> Self {
+        Self { time: SystemTime::UNIX_EPOCH + Duration::from_secs(unix) }
+    }
+}
+
+impl TimeSource for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.time
+    }
+}
+
+/// A clock only moving when told to, for tests stepping through a day.
+///
+/// Clones share the time, so a clone kept by the caller moves the clock
+/// handed to a [`crate::digital_clock::DigitalClock`].
+///
+/// # Fields
+/// * `time` - The time returned by [`TimeSource::now`].
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    time: Arc<Mutex<SystemTime>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// Create a clock standing at some seconds after the Unix epoch.
+    ///
+    /// # Arguments
+    /// * `unix` - The seconds since 1970-01-01 00:00:00 UTC.
+    pub fn at(unix: u64) -> Self {
+        Self { time: Arc::new(Mutex::new(SystemTime::UNIX_EPOCH + Duration::from_secs(unix))) }
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&self, step: Duration) {
+        *self.time.lock().unwrap() += step;
+    }
+}
+
+#[cfg(test)]
+impl TimeSource for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.time.lock().unwrap()
+    }
+}
+
+/// A clock running from a start time at a multiple of real time.
+///
+/// # Fields
+/// * `start` - The time the clock shows when created.
+/// * `origin` - The real instant the clock was created at.
+/// * `speed` - The simulated seconds per real second.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedClock {
+    start: SystemTime,
+    origin: Instant,
+    speed: u32,
+}
+
+impl SimulatedClock {
+    /// Create a clock starting now.
+    ///
+    /// # Arguments
+    /// * `start` - The time the clock shows now.
+    /// * `speed` - The simulated seconds per real second, e.g. 1440 to run a day in a minute.
+    pub fn new(start: SystemTime, speed: u32) -> Self {
+        Self { start, origin: Instant::now(), speed }
+    }
+}
+
+impl TimeSource for SimulatedClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.origin.elapsed() * self.speed
+    }
+}
@@ -0,0 +1,738 @@
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bbb_hal::calendar::{civil_from_days, days_from_civil, days_in_month, is_leap, weekday};
+
+/// Directory of the zoneinfo database, unless `TZDIR` names another one.
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// Zone file used when `TZ` is not set.
+const LOCALTIME_PATH: &str = "/etc/localtime";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Offset from UTC in effect for a span of time.
+///
+/// # Fields
+/// * `utc_offset` - The seconds to add to UTC to get local time.
+/// * `is_dst` - Whether the offset is daylight saving time.
+/// * `abbreviation` - The designation of the offset, e.g. `CEST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalTimeType {
+    pub utc_offset: i32,
+    pub is_dst: bool,
+    pub abbreviation: String,
+}
+
+/// A broken-down local time.
+///
+/// # Fields
+/// * `year` - The year, e.g. 2024.
+/// * `month` - The month, from 1 to 12.
+/// * `day` - The day of the month, from 1 to 31.
+/// * `hour` - The hour, from 0 to 23.
+/// * `minute` - The minute, from 0 to 59.
+/// * `second` - The second, from 0 to 59.
+/// * `weekday` - The day of the week, from 0 (Sunday) to 6 (Saturday).
+/// * `is_dst` - Whether daylight saving time is in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub weekday: u32,
+    pub is_dst: bool,
+}
+
+/// Rule of a POSIX `TZ` string, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`.
+///
+/// # Fields
+/// * `std` - The standard time offset.
+/// * `dst` - The daylight saving time offset and the transition dates, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PosixRule {
+    std: LocalTimeType,
+    dst: Option<(LocalTimeType, RuleDate, RuleDate)>,
+}
+
+/// Day and local time of a daylight saving transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RuleDate {
+    day: RuleDay,
+    time: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDay {
+    /// `Jn`: day 1 to 365, February 29 never counted.
+    Julian(u32),
+    /// `n`: day 0 to 365, February 29 counted in leap years.
+    Ordinal(u32),
+    /// `Mm.w.d`: day `d` (0 is Sunday) of week `w` (5 is the last) of month `m`.
+    MonthWeekDay(u32, u32, u32),
+}
+
+/// Time zone loaded from the zoneinfo database or a POSIX `TZ` string.
+///
+/// Transitions listed in a TZif file are used as is, times past the last one
+/// follow the POSIX rule in the file footer, so DST keeps being applied in
+/// years the file does not list.
+///
+/// # Fields
+/// * `transitions` - The UTC times the offset changes at, in ascending order.
+/// * `transition_types` - The index in `types` of the offset starting at every transition.
+/// * `types` - The offsets of the zone, the first one applying before the first transition.
+/// * `rule` - The rule for times after the last transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    transitions: Vec<i64>,
+    transition_types: Vec<usize>,
+    types: Vec<LocalTimeType>,
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    /// The UTC time zone.
+    pub fn utc() -> Self {
+        Self {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: vec![LocalTimeType { utc_offset: 0, is_dst: false, abbreviation: "UTC".to_string() }],
+            rule: None,
+        }
+    }
+
+    /// Load the time zone of the system, from `TZ` or else `/etc/localtime`, falling back to UTC.
+    ///
+    /// # Returns
+    /// * `Result<TimeZone, Box<dyn Error>>` - The time zone, or an error if `TZ` or the zone file is invalid.
+    pub fn local() -> Result<Self, Box<dyn Error>> {
+        match env::var("TZ") {
+            Ok(spec) => Self::named(&spec),
+            Err(_) if Path::new(LOCALTIME_PATH).exists() => Self::load(LOCALTIME_PATH),
+            Err(_) => Ok(Self::utc()),
+        }
+    }
+
+    /// Load a time zone given like the `TZ` variable.
+    ///
+    /// # Arguments
+    /// * `spec` - A zone name (`Europe/Berlin`), an absolute path to a TZif
+    ///   file or a POSIX rule (`CET-1CEST,M3.5.0,M10.5.0/3`), optionally prefixed with `:`.
+    ///
+    /// # Returns
+    /// * `Result<TimeZone, Box<dyn Error>>` - The time zone, or an error if it cannot be found or parsed.
+    pub fn named(spec: &str) -> Result<Self, Box<dyn Error>> {
+
+        let spec = spec.strip_prefix(':').unwrap_or(spec);
+
+        if spec.is_empty() {
+            return Ok(Self::utc());
+        }
+        if spec.starts_with('/') {
+            return Self::load(spec);
+        }
+
+        let dir = env::var_os("TZDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(ZONEINFO_DIR));
+        let path = dir.join(spec);
+        if !spec.split('/').any(|part| part == "..") && path.is_file() {
+            return Self::load(path);
+        }
+
+        Self::from_posix(spec).map_err(|e| format!("Unknown time zone {}: {}", spec, e).into())
+    }
+
+    /// Load a TZif file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file, e.g. `/usr/share/zoneinfo/Europe/Berlin`.
+    ///
+    /// # Returns
+    /// * `Result<TimeZone, Box<dyn Error>>` - The time zone, or an error if the file cannot be read or parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| format!("Cannot read time zone file {}: {}", path.display(), e))?;
+
+        Self::from_tzif(&bytes).map_err(|e| format!("Invalid time zone file {}: {}", path.display(), e).into())
+    }
+
+    /// Parse the content of a TZif file (RFC 8536), version 1 to 4.
+    ///
+    /// # Arguments
+    /// * `bytes` - The content of the file.
+    ///
+    /// # Returns
+    /// * `Result<TimeZone, Box<dyn Error>>` - The time zone, or an error describing the invalid part.
+    pub fn from_tzif(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+
+        let mut reader = Reader { bytes, pos: 0 };
+        let header = reader.header()?;
+
+        if header.version == 0 {
+            return Self::from_tzif_data(&mut reader, &header, 4, None);
+        }
+
+        // skip the version 1 data, repeated with 64-bit times after a second header
+        reader.skip(header.data_len(4)?)?;
+        let header = reader.header()?;
+        let mut data = reader.clone();
+        data.skip(header.data_len(8)?)?;
+
+        let footer = data.footer()?;
+        let rule = match footer.as_str() {
+            "" => None,
+            text => Some(PosixRule::parse(text)?),
+        };
+
+        Self::from_tzif_data(&mut reader, &header, 8, rule)
+    }
+
+    fn from_tzif_data(reader: &mut Reader, header: &Header, time_size: usize, rule: Option<PosixRule>) -> Result<Self, Box<dyn Error>> {
+
+        // the counts are only trusted once the file is known to hold that much data
+        reader.clone().skip(header.data_len(time_size)?)?;
+
+        let transitions = (0..header.timecnt)
+            .map(|_| reader.int(time_size))
+            .collect::<Result<Vec<_>, _>>()?;
+        let transition_types = (0..header.timecnt)
+            .map(|_| reader.int(1).map(|i| i as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut infos = Vec::with_capacity(header.typecnt);
+        for _ in 0..header.typecnt {
+            let utc_offset = reader.int(4)? as i32;
+            let is_dst = reader.int(1)? != 0;
+            let index = reader.int(1)? as usize;
+            infos.push((utc_offset, is_dst, index));
+        }
+
+        let chars = reader.take(header.charcnt)?;
+        let types = infos
+            .into_iter()
+            .map(|(utc_offset, is_dst, index)| {
+                let name = chars.get(index..).ok_or("Abbreviation index out of range")?;
+                let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                let abbreviation = String::from_utf8_lossy(&name[..end]).into_owned();
+                Ok(LocalTimeType { utc_offset, is_dst, abbreviation })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        if types.is_empty() {
+            return Err("No local time type".into());
+        }
+        if transition_types.iter().any(|&i| i >= types.len()) {
+            return Err("Transition type index out of range".into());
+        }
+        if transitions.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Transition times not in ascending order".into());
+        }
+
+        Ok(Self { transitions, transition_types, types, rule })
+    }
+
+    /// Parse a POSIX `TZ` rule.
+    ///
+    /// # Arguments
+    /// * `spec` - The rule, e.g. `EST5EDT,M3.2.0,M11.1.0` or `<+0530>-5:30`.
+    ///
+    /// # Returns
+    /// * `Result<TimeZone, Box<dyn Error>>` - The time zone, or an error describing the invalid part.
+    pub fn from_posix(spec: &str) -> Result<Self, Box<dyn Error>> {
+
+        let rule = PosixRule::parse(spec)?;
+
+        Ok(Self {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: vec![rule.std.clone()],
+            rule: Some(rule),
+        })
+    }
+
+    /// Get the offset in effect at a time.
+    ///
+    /// # Arguments
+    /// * `unix` - The seconds since 1970-01-01 00:00:00 UTC.
+    ///
+    /// # Returns
+    /// * `LocalTimeType` - The offset from UTC in effect.
+    pub fn find(&self, unix: i64) -> LocalTimeType {
+
+        match self.transitions.last() {
+            Some(&last) if unix >= last => match &self.rule {
+                Some(rule) => rule.find(unix),
+                None => self.types[self.transition_types[self.transitions.len() - 1]].clone(),
+            },
+            Some(_) => {
+                let index = self.transitions.partition_point(|&t| t <= unix);
+                match index {
+                    0 => self.types[0].clone(),
+                    i => self.types[self.transition_types[i - 1]].clone(),
+                }
+            },
+            None => match &self.rule {
+                Some(rule) => rule.find(unix),
+                None => self.types[0].clone(),
+            },
+        }
+    }
+
+    /// Convert a time to local time.
+    ///
+    /// # Arguments
+    /// * `unix` - The seconds since 1970-01-01 00:00:00 UTC.
+    ///
+    /// # Returns
+    /// * `LocalTime` - The broken-down local time.
+    pub fn to_local(&self, unix: i64) -> LocalTime {
+
+        let offset = self.find(unix);
+        let local = unix + offset.utc_offset as i64;
+
+        let days = local.div_euclid(SECONDS_PER_DAY);
+        let seconds = local.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        LocalTime {
+            year,
+            month,
+            day,
+            hour: (seconds / 3600) as u32,
+            minute: (seconds % 3600 / 60) as u32,
+            second: (seconds % 60) as u32,
+            weekday: weekday(days),
+            is_dst: offset.is_dst,
+        }
+    }
+}
+
+impl PosixRule {
+    fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+
+        let mut parser = PosixParser { text: spec.as_bytes(), pos: 0 };
+
+        let std_name = parser.name()?;
+        // `UTC` and `GMT` are accepted without their zero offset
+        let std_offset = match std_name.as_str() {
+            "UTC" | "GMT" if parser.done() => 0,
+            _ => -parser.offset()?,
+        };
+        let std = LocalTimeType { utc_offset: std_offset as i32, is_dst: false, abbreviation: std_name };
+
+        if parser.done() {
+            return Ok(Self { std, dst: None });
+        }
+
+        let dst_name = parser.name()?;
+        let dst_offset = match parser.peek() {
+            Some(b',') | None => std_offset + 3600,
+            _ => -parser.offset()?,
+        };
+        let dst = LocalTimeType { utc_offset: dst_offset as i32, is_dst: true, abbreviation: dst_name };
+
+        // US rules when the transitions are not given
+        let (start, end) = if parser.done() {
+            (
+                RuleDate { day: RuleDay::MonthWeekDay(3, 2, 0), time: 7200 },
+                RuleDate { day: RuleDay::MonthWeekDay(11, 1, 0), time: 7200 },
+            )
+        } else {
+            parser.expect(b',')?;
+            let start = parser.rule_date()?;
+            parser.expect(b',')?;
+            let end = parser.rule_date()?;
+            (start, end)
+        };
+
+        if !parser.done() {
+            return Err(format!("Unexpected text after rule: {:?}", &spec[parser.pos..]).into());
+        }
+
+        Ok(Self { std, dst: Some((dst, start, end)) })
+    }
+
+    fn find(&self, unix: i64) -> LocalTimeType {
+
+        let Some((dst, start, end)) = &self.dst else {
+            return self.std.clone();
+        };
+
+        let std_offset = self.std.utc_offset as i64;
+        let dst_offset = dst.utc_offset as i64;
+        let (year, _, _) = civil_from_days((unix + std_offset).div_euclid(SECONDS_PER_DAY));
+
+        // the start is given in standard time, the end in daylight saving time
+        let start = start.local_time(year) - std_offset;
+        let end = end.local_time(year) - dst_offset;
+
+        let in_dst = if start < end {
+            start <= unix && unix < end
+        } else {
+            !(end <= unix && unix < start)
+        };
+
+        if in_dst { dst.clone() } else { self.std.clone() }
+    }
+}
+
+impl RuleDate {
+    /// Seconds from 1970-01-01 00:00:00 to the transition, in the local time of the given year.
+    fn local_time(&self, year: i64) -> i64 {
+
+        let jan_1 = days_from_civil(year, 1, 1);
+        let days = match self.day {
+            RuleDay::Julian(n) => jan_1 + n as i64 - 1 + (is_leap(year) && n >= 60) as i64,
+            RuleDay::Ordinal(n) => jan_1 + n as i64,
+            RuleDay::MonthWeekDay(month, week, wday) => {
+                let first = days_from_civil(year, month, 1);
+                let mut day = (wday as i64 - weekday(first) as i64).rem_euclid(7) + (week as i64 - 1) * 7;
+                while day >= days_in_month(year, month) as i64 {
+                    day -= 7;
+                }
+                first + day
+            },
+        };
+
+        days * SECONDS_PER_DAY + self.time
+    }
+}
+
+/// TZif header fields used to size the data block.
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl Header {
+    fn data_len(&self, time_size: usize) -> Result<usize, Box<dyn Error>> {
+        let sizes = [
+            self.timecnt.checked_mul(time_size + 1),
+            self.typecnt.checked_mul(6),
+            Some(self.charcnt),
+            self.leapcnt.checked_mul(time_size + 4),
+            Some(self.isstdcnt),
+            Some(self.isutcnt),
+        ];
+        sizes
+            .into_iter()
+            .try_fold(0usize, |total, size| total.checked_add(size?))
+            .ok_or_else(|| "Data block size out of range".into())
+    }
+}
+
+#[derive(Clone)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self.pos.checked_add(len).ok_or("Length out of range")?;
+        let bytes = self.bytes
+            .get(self.pos..end)
+            .ok_or_else(|| format!("Truncated at byte {}", self.pos))?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), Box<dyn Error>> {
+        self.take(len).map(|_| ())
+    }
+
+    /// Read a big-endian signed integer of 1, 4 or 8 bytes.
+    fn int(&mut self, size: usize) -> Result<i64, Box<dyn Error>> {
+        let bytes = self.take(size)?;
+        Ok(match size {
+            1 => bytes[0] as i64,
+            4 => i32::from_be_bytes(bytes.try_into()?) as i64,
+            _ => i64::from_be_bytes(bytes.try_into()?),
+        })
+    }
+
+    fn header(&mut self) -> Result<Header, Box<dyn Error>> {
+
+        if self.take(4)? != b"TZif" {
+            return Err("Missing TZif magic".into());
+        }
+        let version = match self.take(1)?[0] {
+            0 => 0,
+            c @ b'2'..=b'4' => c - b'0',
+            c => return Err(format!("Unsupported TZif version {:?}", c as char).into()),
+        };
+        self.skip(15)?;
+
+        let mut count = || -> Result<usize, Box<dyn Error>> {
+            let n = self.int(4)?;
+            usize::try_from(n).map_err(|_| format!("Invalid count {}", n).into())
+        };
+        Ok(Header {
+            version,
+            isutcnt: count()?,
+            isstdcnt: count()?,
+            leapcnt: count()?,
+            timecnt: count()?,
+            typecnt: count()?,
+            charcnt: count()?,
+        })
+    }
+
+    fn footer(&mut self) -> Result<String, Box<dyn Error>> {
+
+        let rest = &self.bytes[self.pos..];
+        let text = rest
+            .strip_prefix(b"\n")
+            .and_then(|rest| rest.iter().position(|&c| c == b'\n').map(|end| &rest[..end]))
+            .ok_or("Missing footer")?;
+
+        Ok(String::from_utf8(text.to_vec())?)
+    }
+}
+
+struct PosixParser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl PosixParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Box<dyn Error>> {
+        if self.peek() != Some(c) {
+            return Err(format!("Expected {:?} at position {}", c as char, self.pos).into());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Parse a zone designation, either alphabetic or quoted in `<>`.
+    fn name(&mut self) -> Result<String, Box<dyn Error>> {
+
+        let start = self.pos;
+        let name = if self.peek() == Some(b'<') {
+            self.pos += 1;
+            while self.peek().is_some_and(|c| c != b'>') {
+                self.pos += 1;
+            }
+            self.expect(b'>')?;
+            &self.text[start + 1..self.pos - 1]
+        } else {
+            while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                self.pos += 1;
+            }
+            &self.text[start..self.pos]
+        };
+
+        if name.len() < 3 {
+            return Err(format!("Invalid zone name at position {}", start).into());
+        }
+
+        Ok(String::from_utf8_lossy(name).into_owned())
+    }
+
+    fn number(&mut self) -> Result<i64, Box<dyn Error>> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.pos])?
+            .parse()
+            .map_err(|_| format!("Expected a number at position {}", start).into())
+    }
+
+    /// Parse `[+-]hh[:mm[:ss]]` in seconds.
+    fn offset(&mut self) -> Result<i64, Box<dyn Error>> {
+
+        let sign = match self.peek() {
+            Some(b'-') => { self.pos += 1; -1 },
+            Some(b'+') => { self.pos += 1; 1 },
+            _ => 1,
+        };
+
+        // hours up to 167, as RFC 8536 allows for transition times
+        let mut seconds = self.bounded(0, 167)? as i64 * 3600;
+        for unit in [60, 1] {
+            if self.peek() != Some(b':') {
+                break;
+            }
+            self.pos += 1;
+            seconds += self.bounded(0, 59)? as i64 * unit;
+        }
+
+        Ok(sign * seconds)
+    }
+
+    fn rule_date(&mut self) -> Result<RuleDate, Box<dyn Error>> {
+
+        let day = match self.peek() {
+            Some(b'J') => {
+                self.pos += 1;
+                RuleDay::Julian(self.bounded(1, 365)?)
+            },
+            Some(b'M') => {
+                self.pos += 1;
+                let month = self.bounded(1, 12)?;
+                self.expect(b'.')?;
+                let week = self.bounded(1, 5)?;
+                self.expect(b'.')?;
+                RuleDay::MonthWeekDay(month, week, self.bounded(0, 6)?)
+            },
+            _ => RuleDay::Ordinal(self.bounded(0, 365)?),
+        };
+
+        let time = if self.peek() == Some(b'/') {
+            self.pos += 1;
+            self.offset()?
+        } else {
+            7200
+        };
+
+        Ok(RuleDate { day, time })
+    }
+
+    fn bounded(&mut self, min: u32, max: u32) -> Result<u32, Box<dyn Error>> {
+        let start = self.pos;
+        let n = self.number()?;
+        if n < min as i64 || n > max as i64 {
+            return Err(format!("Value {} out of range {}..={} at position {}", n, min, max, start).into());
+        }
+        Ok(n as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026 transitions of Central European Time: to CEST on March 29, back to CET on October 25.
+    const TRANSITIONS: [(i64, u8); 2] = [(1_774_746_000, 1), (1_792_890_000, 0)];
+
+    /// 2027 transitions, past the listed ones.
+    const DST_2027: i64 = 1_806_195_600;
+    const STD_2027: i64 = 1_824_944_400;
+
+    /// Data block with the CET and CEST types and the transitions, with times of `time_size` bytes.
+    fn block(version: u8, time_size: usize) -> Vec<u8> {
+        let mut bytes = b"TZif".to_vec();
+        bytes.push(version);
+        bytes.extend([0; 15]);
+        for count in [0, 0, 0, TRANSITIONS.len() as u32, 2, 9] {
+            bytes.extend(count.to_be_bytes());
+        }
+        for (time, _) in TRANSITIONS {
+            bytes.extend(&time.to_be_bytes()[8 - time_size..]);
+        }
+        bytes.extend(TRANSITIONS.map(|(_, index)| index));
+        bytes.extend(3600i32.to_be_bytes());
+        bytes.extend([0, 0]);
+        bytes.extend(7200i32.to_be_bytes());
+        bytes.extend([1, 4]);
+        bytes.extend(b"CET\0CEST\0");
+        bytes
+    }
+
+    fn tzif_v1() -> Vec<u8> {
+        block(0, 4)
+    }
+
+    fn tzif_v2() -> Vec<u8> {
+        let mut bytes = block(b'2', 4);
+        bytes.extend(block(b'2', 8));
+        bytes.extend(b"\nCET-1CEST,M3.5.0,M10.5.0/3\n");
+        bytes
+    }
+
+    fn abbreviation(zone: &TimeZone, unix: i64) -> String {
+        zone.find(unix).abbreviation
+    }
+
+    #[test]
+    fn v1_transitions() {
+        let zone = TimeZone::from_tzif(&tzif_v1()).unwrap();
+        let [(to_dst, _), (to_std, _)] = TRANSITIONS;
+
+        assert_eq!(abbreviation(&zone, to_dst - 1), "CET");
+        assert_eq!(abbreviation(&zone, to_dst), "CEST");
+        assert_eq!(abbreviation(&zone, to_std - 1), "CEST");
+        assert_eq!(abbreviation(&zone, to_std), "CET");
+        // without a footer the last offset stays in effect
+        assert_eq!(abbreviation(&zone, DST_2027), "CET");
+
+        let local = zone.to_local(to_dst);
+        assert_eq!((local.hour, local.minute, local.is_dst), (3, 0, true));
+        let local = zone.to_local(to_dst - 1);
+        assert_eq!((local.hour, local.minute, local.second, local.is_dst), (1, 59, 59, false));
+    }
+
+    #[test]
+    fn v2_transitions_and_footer() {
+        let zone = TimeZone::from_tzif(&tzif_v2()).unwrap();
+        let [(to_dst, _), (to_std, _)] = TRANSITIONS;
+
+        assert_eq!(abbreviation(&zone, to_dst - 1), "CET");
+        assert_eq!(abbreviation(&zone, to_dst), "CEST");
+        assert_eq!(abbreviation(&zone, to_std - 1), "CEST");
+        assert_eq!(abbreviation(&zone, to_std), "CET");
+
+        // 2027 follows the footer rule
+        assert_eq!(abbreviation(&zone, DST_2027 - 1), "CET");
+        assert_eq!(abbreviation(&zone, DST_2027), "CEST");
+        assert_eq!(abbreviation(&zone, STD_2027 - 1), "CEST");
+        assert_eq!(abbreviation(&zone, STD_2027), "CET");
+
+        let local = zone.to_local(STD_2027);
+        assert_eq!((local.month, local.day, local.hour, local.is_dst), (10, 31, 2, false));
+        let local = zone.to_local(STD_2027 - 1);
+        assert_eq!((local.hour, local.minute, local.second, local.is_dst), (2, 59, 59, true));
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        for bytes in [tzif_v1(), tzif_v2()] {
+            for len in 0..bytes.len() {
+                assert!(TimeZone::from_tzif(&bytes[..len]).is_err(), "length {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected() {
+        // timecnt of the first header
+        for count in [u32::MAX, i32::MAX as u32, 0x1000_0000] {
+            for mut bytes in [tzif_v1(), tzif_v2()] {
+                bytes[32..36].copy_from_slice(&count.to_be_bytes());
+                assert!(TimeZone::from_tzif(&bytes).is_err(), "count {:#x}", count);
+            }
+        }
+    }
+
+    #[test]
+    fn utc_and_gmt_without_offset() {
+        for spec in ["UTC", "GMT", "UTC0"] {
+            let zone = TimeZone::from_posix(spec).unwrap();
+            assert_eq!(zone.find(DST_2027).utc_offset, 0, "{}", spec);
+        }
+        assert!(TimeZone::from_posix("CET").is_err());
+        assert!(TimeZone::from_posix("CET99999999999999999999").is_err());
+    }
+}